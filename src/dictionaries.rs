@@ -0,0 +1,86 @@
+use crate::errors::ParseError;
+use crate::jmdict::JMDict;
+use crate::jmnedict::JMnedict;
+use crate::kanjidic::Kanjidic;
+use std::fs;
+use std::path::Path;
+
+/// The dictionaries found in a directory by `Dictionaries::load_dir`. Each is
+/// `None` if the directory had no file of that kind.
+#[derive(Debug, Default)]
+pub struct Dictionaries {
+    pub kanjidic: Option<Kanjidic>,
+    pub jmdict: Option<JMDict>,
+    pub jmnedict: Option<JMnedict>,
+}
+
+impl Dictionaries {
+    /// Parses each file in the directory as the dictionary named by its root
+    /// element: kanjidic2, JMdict, or JMnedict. Files are detected by content
+    /// rather than name, so the extensionless JMdict_e of the distribution is
+    /// found too. Files with other roots or that aren't UTF-8, such as the
+    /// compressed downloads, are skipped, as are the subdirectories. It is an
+    /// error for two files to have the same kind.
+    pub fn load_dir<P: AsRef<Path>>(dirpath: P) -> Result<Self, ParseError> {
+        let dirpath = dirpath.as_ref();
+        let mut paths = Vec::new();
+        for dir_entry in fs::read_dir(dirpath).map_err(|err| ParseError::io(dirpath, err))? {
+            let path = dir_entry
+                .map_err(|err| ParseError::io(dirpath, err))?
+                .path();
+            if path.is_file() {
+                paths.push(path);
+            }
+        }
+        // Sorted so that errors don't depend on the directory order.
+        paths.sort();
+
+        let mut dicts = Dictionaries::default();
+        for path in paths {
+            let bytes = fs::read(&path).map_err(|err| ParseError::io(&path, err))?;
+            let contents = match std::str::from_utf8(&bytes) {
+                Ok(contents) => contents,
+                Err(_) => continue,
+            };
+            match root_name(contents) {
+                Some("kanjidic2") => set_once(&mut dicts.kanjidic, &path, || contents.parse())?,
+                Some("JMdict") => set_once(&mut dicts.jmdict, &path, || contents.parse())?,
+                Some("JMnedict") => set_once(&mut dicts.jmnedict, &path, || contents.parse())?,
+                _ => {}
+            }
+        }
+        Ok(dicts)
+    }
+}
+
+fn set_once<T, F>(slot: &mut Option<T>, path: &Path, parse: F) -> Result<(), ParseError>
+where
+    F: FnOnce() -> Result<T, ParseError>,
+{
+    if slot.is_some() {
+        return Err(ParseError::ParseString(format!(
+            "more than one dictionary of the same kind: {}",
+            path.display()
+        )));
+    }
+    *slot = Some(parse()?);
+    Ok(())
+}
+
+// Finds the name of the root element without parsing the whole document, by
+// skipping the XML declaration, comments, and the DOCTYPE with its internal
+// subset, all of which start with "<?" or "<!".
+fn root_name(contents: &str) -> Option<&str> {
+    let mut rest = contents;
+    loop {
+        rest = &rest[rest.find('<')? + 1..];
+        if rest.starts_with("!--") {
+            rest = &rest[rest.find("-->")? + 3..];
+        } else if !rest.starts_with('?') && !rest.starts_with('!') {
+            let end = rest
+                .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+                .unwrap_or(rest.len());
+            return Some(&rest[..end]);
+        }
+    }
+}
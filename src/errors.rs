@@ -1,24 +1,43 @@
-use roxmltree;
+use roxmltree::{self, TextPos};
 use std::error;
 use std::fmt;
 use std::io;
 use std::num;
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::path::PathBuf;
+use std::str;
 
 #[derive(Debug)]
 pub enum ParseError {
-    IO(io::Error),
+    /// The path is that of the file being read, if the error came from opening
+    /// or reading one.
+    Io {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
     Xml(XmlError),
     ParseString(String),
     ParseInt(num::ParseIntError),
     ParseEnum(ParseEnumError),
     ParseLanguage6391(String),
     ParseLanguage6393(String),
+    Utf8(str::Utf8Error),
+    #[cfg(feature = "download")]
+    Http(reqwest::Error),
 }
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            ParseError::IO(ref err) => write!(f, "IO error: {}", err),
+            ParseError::Io {
+                path: Some(ref path),
+                ref source,
+            } => write!(f, "IO error: {}: {}", path.display(), source),
+            ParseError::Io {
+                path: None,
+                ref source,
+            } => write!(f, "IO error: {}", source),
             ParseError::Xml(ref err) => write!(f, "XML error: {}", err),
             ParseError::ParseString(ref err_str) => write!(f, "Parse error: {}", err_str),
             ParseError::ParseInt(ref err) => write!(f, "Parse error: {}", err),
@@ -29,6 +48,9 @@ impl fmt::Display for ParseError {
             ParseError::ParseLanguage6393(ref lang) => {
                 write!(f, "invalid ISO 639-3 language code: {}", lang)
             }
+            ParseError::Utf8(ref err) => write!(f, "UTF-8 error: {}", err),
+            #[cfg(feature = "download")]
+            ParseError::Http(ref err) => write!(f, "HTTP error: {}", err),
         }
     }
 }
@@ -36,10 +58,13 @@ impl fmt::Display for ParseError {
 impl error::Error for ParseError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
-            ParseError::IO(ref err) => Some(err),
+            ParseError::Io { ref source, .. } => Some(source),
             ParseError::Xml(ref err) => Some(err),
             ParseError::ParseInt(ref err) => Some(err),
             ParseError::ParseEnum(ref err) => Some(err),
+            ParseError::Utf8(ref err) => Some(err),
+            #[cfg(feature = "download")]
+            ParseError::Http(ref err) => Some(err),
             _ => None,
         }
     }
@@ -47,7 +72,20 @@ impl error::Error for ParseError {
 
 impl From<io::Error> for ParseError {
     fn from(err: io::Error) -> Self {
-        ParseError::IO(err)
+        ParseError::Io {
+            path: None,
+            source: err,
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl ParseError {
+    pub(crate) fn io(path: impl AsRef<Path>, source: io::Error) -> Self {
+        ParseError::Io {
+            path: Some(path.as_ref().to_owned()),
+            source,
+        }
     }
 }
 
@@ -57,12 +95,31 @@ impl From<XmlError> for ParseError {
     }
 }
 
+impl From<roxmltree::Error> for ParseError {
+    fn from(err: roxmltree::Error) -> Self {
+        ParseError::Xml(XmlError::Roxml(err))
+    }
+}
+
 impl From<num::ParseIntError> for ParseError {
     fn from(err: num::ParseIntError) -> Self {
         ParseError::ParseInt(err)
     }
 }
 
+impl From<str::Utf8Error> for ParseError {
+    fn from(err: str::Utf8Error) -> Self {
+        ParseError::Utf8(err)
+    }
+}
+
+#[cfg(feature = "download")]
+impl From<reqwest::Error> for ParseError {
+    fn from(err: reqwest::Error) -> Self {
+        ParseError::Http(err)
+    }
+}
+
 impl From<ParseEnumError> for ParseError {
     fn from(err: ParseEnumError) -> Self {
         ParseError::ParseEnum(err)
@@ -72,18 +129,29 @@ impl From<ParseEnumError> for ParseError {
 #[derive(Debug)]
 pub enum XmlError {
     Roxml(roxmltree::Error),
-    MissingTag(String),
+    /// The position is that of the element the tag was expected in.
+    MissingTag {
+        tag: String,
+        pos: Option<TextPos>,
+    },
     MissingAttr(String),
     MissingText,
+    #[cfg(feature = "streaming")]
+    QuickXml(quick_xml::Error),
 }
 
 impl fmt::Display for XmlError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             XmlError::Roxml(ref err) => write!(f, "roxmltree error: {}", err),
-            XmlError::MissingTag(ref name) => write!(f, "XML tag missing: {}", name),
+            XmlError::MissingTag { ref tag, pos } => match pos {
+                Some(pos) => write!(f, "XML tag missing: {} (in element at {})", tag, pos),
+                None => write!(f, "XML tag missing: {}", tag),
+            },
             XmlError::MissingAttr(ref name) => write!(f, "XML element attribute missing: {}", name),
             XmlError::MissingText => write!(f, "XML element text missing"),
+            #[cfg(feature = "streaming")]
+            XmlError::QuickXml(ref err) => write!(f, "quick-xml error: {}", err),
         }
     }
 }
@@ -92,6 +160,8 @@ impl error::Error for XmlError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
             XmlError::Roxml(ref err) => Some(err),
+            #[cfg(feature = "streaming")]
+            XmlError::QuickXml(ref err) => Some(err),
             _ => None,
         }
     }
@@ -103,6 +173,13 @@ impl From<roxmltree::Error> for XmlError {
     }
 }
 
+#[cfg(feature = "streaming")]
+impl From<quick_xml::Error> for XmlError {
+    fn from(error: quick_xml::Error) -> Self {
+        XmlError::QuickXml(error)
+    }
+}
+
 #[derive(Debug)]
 pub struct ParseEnumError {
     value: String,
@@ -130,3 +207,71 @@ impl fmt::Display for ParseEnumError {
 }
 
 impl error::Error for ParseEnumError {}
+
+#[derive(Debug)]
+pub enum ParseWarning {
+    UnknownEnum(ParseEnumError),
+    /// The name of an element that was skipped.
+    UnknownTag(String),
+    /// A required element missing from the character with the literal, if it
+    /// has one.
+    MissingElement {
+        literal: Option<String>,
+        tag: String,
+    },
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseWarning::UnknownEnum(ref err) => write!(f, "Unknown value skipped: {}", err),
+            ParseWarning::UnknownTag(ref tag) => write!(f, "Unknown tag skipped: {}", tag),
+            ParseWarning::MissingElement {
+                literal: Some(ref literal),
+                ref tag,
+            } => write!(f, "{}: required tag missing: {}", literal, tag),
+            ParseWarning::MissingElement {
+                literal: None,
+                ref tag,
+            } => write!(f, "Required tag missing: {}", tag),
+        }
+    }
+}
+
+impl error::Error for ParseWarning {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ParseWarning::UnknownEnum(ref err) => Some(err),
+            ParseWarning::UnknownTag(_) | ParseWarning::MissingElement { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ValidationError {
+    ZeroStrokeCount(String),
+    InvalidStrokeMiscount(String, u32),
+    MissingCodepoints(String),
+    MissingReadings(String),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ValidationError::ZeroStrokeCount(ref literal) => {
+                write!(f, "{}: stroke count is zero", literal)
+            }
+            ValidationError::InvalidStrokeMiscount(ref literal, count) => {
+                write!(f, "{}: invalid stroke miscount {}", literal, count)
+            }
+            ValidationError::MissingCodepoints(ref literal) => {
+                write!(f, "{}: no codepoints", literal)
+            }
+            ValidationError::MissingReadings(ref literal) => {
+                write!(f, "{}: graded kanji has no on or kun readings", literal)
+            }
+        }
+    }
+}
+
+impl error::Error for ValidationError {}
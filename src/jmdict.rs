@@ -1,6 +1,9 @@
-use crate::errors::{ParseEnumError, ParseError, XmlError};
-use crate::util::{self, find_child_tag, find_child_tag_err, get_node_text, ns_xml_attr};
+use crate::errors::{ParseEnumError, ParseError};
+#[cfg(feature = "fs")]
+use crate::util;
+use crate::util::{find_child_tag, find_child_tag_err, get_node_text, missing_tag, ns_xml_attr};
 use roxmltree::{Document, Node};
+use std::str::FromStr;
 
 #[derive(Debug)]
 pub struct JMDict {
@@ -98,7 +101,7 @@ impl JMDict {
     {
         self.entries
             .iter()
-            .filter(|e| e.reading.iter().any(|r| predicate(r)))
+            .filter(|e| e.reading.iter().any(&predicate))
             .collect()
     }
 
@@ -108,7 +111,7 @@ impl JMDict {
     {
         self.entries
             .iter()
-            .filter(|e| e.kanji.iter().any(|k| predicate(k)))
+            .filter(|e| e.kanji.iter().any(&predicate))
             .collect()
     }
 
@@ -118,7 +121,7 @@ impl JMDict {
     {
         self.entries
             .iter()
-            .filter(|e| e.sense.iter().flat_map(|s| &s.gloss).any(|g| predicate(g)))
+            .filter(|e| e.sense.iter().flat_map(|s| &s.gloss).any(&predicate))
             .collect()
     }
 
@@ -134,14 +137,23 @@ impl JMDict {
 
     pub fn antonyms(&self, entry: &Entry) -> Vec<&Entry> {
         let ant = entry.sense.iter().flat_map(|s| &s.antonyms);
-        ant.flat_map(|a| self.search(&a)).collect()
+        ant.flat_map(|a| self.search(a)).collect()
     }
 }
 
 impl JMDict {
+    #[cfg(feature = "fs")]
     pub fn from_file(filepath: &str) -> Result<Self, ParseError> {
         let contents = util::read_file(filepath)?;
-        let doc = Document::parse(&contents).map_err(XmlError::Roxml)?;
+        contents.parse()
+    }
+}
+
+impl FromStr for JMDict {
+    type Err = ParseError;
+
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let doc = Document::parse(contents)?;
 
         let entries: Vec<_> = doc
             .root_element()
@@ -171,7 +183,7 @@ fn parse_entry(n: Node) -> Result<Entry, ParseError> {
 
         match seq_text {
             Some(t) => t.parse()?,
-            None => return Err(XmlError::MissingTag(SEQ.to_owned()).into()),
+            None => return Err(missing_tag(n, SEQ).into()),
         }
     };
 
@@ -206,7 +218,7 @@ const_strs!(
     READING_INF: "re_inf"
 );
 
-fn parse_reading(n: Node) -> Result<Reading, ParseError> {
+pub(crate) fn parse_reading(n: Node) -> Result<Reading, ParseError> {
     let mut reb_op: Option<String> = None;
     let mut re_pri: Option<PriRef> = None;
     let mut restrict = Vec::new();
@@ -226,7 +238,7 @@ fn parse_reading(n: Node) -> Result<Reading, ParseError> {
         }
     }
 
-    let reb = reb_op.ok_or(XmlError::MissingTag(READING_TEXT.to_owned()))?;
+    let reb = reb_op.ok_or_else(|| missing_tag(n, READING_TEXT))?;
 
     Ok(Reading {
         text: reb,
@@ -241,7 +253,7 @@ const_strs!(
     KANJI_PRI: "ke_pri",
 );
 
-fn parse_kanji(n: Node) -> Result<Kanji, ParseError> {
+pub(crate) fn parse_kanji(n: Node) -> Result<Kanji, ParseError> {
     let keb_node = find_child_tag_err(n, KANJI_TEXT)?;
     let keb = get_node_text(keb_node)?;
 
@@ -335,13 +347,13 @@ fn parse_sense(n: Node) -> Result<Sense, ParseError> {
             DIALECT => sense.dialects.push(text?.into_owned()),
             INFO => sense.info.push(text?.into_owned()),
             LSOURCE => {
-                let content = text.ok().and_then(|t| Some(t.into_owned()));
+                let content = text.ok().map(|t| t.into_owned());
                 let lang = c
                     .attribute(ns_xml_attr(LSOURCE_LANG_SUFFIX))
-                    .unwrap_or_else(|| LSOURCE_LANG_DEF)
+                    .unwrap_or(LSOURCE_LANG_DEF)
                     .to_owned();
-                let full = c.attribute(LSOURCE_TYPE).map_or(true, |_| false);
-                let wasei = c.attribute(LSOURCE_WASEI).map_or(false, |_| true);
+                let full = c.attribute(LSOURCE_TYPE).is_none();
+                let wasei = c.attribute(LSOURCE_WASEI).is_some();
 
                 sense.source_lang.push(LSource {
                     content,
@@ -351,13 +363,13 @@ fn parse_sense(n: Node) -> Result<Sense, ParseError> {
                 });
             }
             GLOSS => {
-                let content = text.ok().and_then(|t| Some(t.into_owned()));
+                let content = text.ok().map(|t| t.into_owned());
                 let lang = c
                     .attribute(ns_xml_attr(GLOSS_LANG_SUFFIX))
-                    .unwrap_or_else(|| GLOSS_LANG_DEFAULT)
+                    .unwrap_or(GLOSS_LANG_DEFAULT)
                     .to_owned();
-                let gender = c.attribute(GLOSS_GENDER).and_then(|g| Some(g.to_owned()));
-                let typ = c.attribute(GLOSS_TYPE).and_then(|t| Some(t.to_owned()));
+                let gender = c.attribute(GLOSS_GENDER).map(|g| g.to_owned());
+                let typ = c.attribute(GLOSS_TYPE).map(|t| t.to_owned());
                 sense.gloss.push(Gloss {
                     content,
                     lang,
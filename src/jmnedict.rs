@@ -0,0 +1,162 @@
+use crate::errors::ParseError;
+use crate::jmdict::{parse_kanji, parse_reading, Kanji, Reading};
+#[cfg(feature = "fs")]
+use crate::util;
+use crate::util::{find_child_tag, get_node_text, missing_tag, ns_xml_attr};
+use roxmltree::{Document, Node};
+use std::str::FromStr;
+
+/// The JMnedict dictionary of proper names. Entries share the k_ele and r_ele
+/// elements of JMdict, but have trans elements with the translations of the
+/// name in place of senses.
+#[derive(Debug)]
+pub struct JMnedict {
+    pub entries: Vec<NameEntry>,
+}
+
+#[derive(Debug)]
+pub struct NameEntry {
+    pub seq: u32,
+    pub kanji: Vec<Kanji>,
+    pub reading: Vec<Reading>,
+    pub trans: Vec<Translation>,
+}
+
+#[derive(Debug)]
+pub struct Translation {
+    /// The expanded name_type entities, e.g. "family or surname" for &surname;.
+    pub name_types: Vec<String>,
+    pub cross_refs: Vec<String>,
+    pub details: Vec<TransDetail>,
+}
+
+#[derive(Debug)]
+pub struct TransDetail {
+    pub content: String,
+    pub lang: String,
+}
+
+impl JMnedict {
+    pub fn find_seq(&self, seq: u32) -> Option<&NameEntry> {
+        self.entries.iter().find(|e| e.seq == seq)
+    }
+
+    pub fn filter<F>(&self, predicate: F) -> Vec<&NameEntry>
+    where
+        F: Fn(&NameEntry) -> bool,
+    {
+        self.entries.iter().filter(|e| predicate(e)).collect()
+    }
+
+    pub fn search(&self, phrase: &str) -> Vec<&NameEntry> {
+        self.filter(|e| {
+            e.reading.iter().any(|r| r.text == phrase) || e.kanji.iter().any(|k| k.text == phrase)
+        })
+    }
+}
+
+impl NameEntry {
+    /// Iterates over the translations of all trans elements in the language,
+    /// e.g. "eng", in document order.
+    pub fn translations<'a>(&'a self, lang: &'a str) -> impl Iterator<Item = &'a str> {
+        self.trans
+            .iter()
+            .flat_map(|t| &t.details)
+            .filter(move |d| d.lang == lang)
+            .map(|d| d.content.as_str())
+    }
+}
+
+impl JMnedict {
+    #[cfg(feature = "fs")]
+    pub fn from_file(filepath: &str) -> Result<Self, ParseError> {
+        let contents = util::read_file(filepath)?;
+        contents.parse()
+    }
+}
+
+impl FromStr for JMnedict {
+    type Err = ParseError;
+
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let doc = Document::parse(contents)?;
+
+        let entries: Vec<_> = doc
+            .root_element()
+            .children()
+            .filter(|n| n.is_element())
+            .map(|n| parse_entry(n))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(JMnedict { entries })
+    }
+}
+
+const_strs!(
+    SEQ: "ent_seq",
+    KANJI_ELE: "k_ele",
+    READING_ELE: "r_ele",
+    TRANS: "trans",
+);
+
+fn parse_entry(n: Node) -> Result<NameEntry, ParseError> {
+    let mut kanji = Vec::new();
+    let mut reading = Vec::new();
+    let mut trans = Vec::new();
+
+    let seq: u32 = match find_child_tag(n, SEQ).and_then(|t| t.text()) {
+        Some(t) => t.parse()?,
+        None => return Err(missing_tag(n, SEQ).into()),
+    };
+
+    for c in n.children() {
+        match c.tag_name().name() {
+            KANJI_ELE => kanji.push(parse_kanji(c)?),
+            READING_ELE => reading.push(parse_reading(c)?),
+            TRANS => trans.push(parse_trans(c)?),
+            _ => {}
+        }
+    }
+
+    Ok(NameEntry {
+        seq,
+        kanji,
+        reading,
+        trans,
+    })
+}
+
+const_strs!(
+    NAME_TYPE: "name_type",
+    CROSS_REF: "xref",
+    TRANS_DET: "trans_det",
+    TRANS_DET_LANG_SUFFIX: "lang",
+    TRANS_DET_LANG_DEFAULT: "eng",
+);
+
+fn parse_trans(n: Node) -> Result<Translation, ParseError> {
+    let mut trans = Translation {
+        name_types: Vec::new(),
+        cross_refs: Vec::new(),
+        details: Vec::new(),
+    };
+
+    for c in n.children() {
+        let tag = c.tag_name().name();
+        match tag {
+            NAME_TYPE => trans.name_types.push(get_node_text(c)?.into_owned()),
+            CROSS_REF => trans.cross_refs.push(get_node_text(c)?.into_owned()),
+            TRANS_DET => {
+                let content = get_node_text(c)?.into_owned();
+                let lang = c
+                    .attribute(ns_xml_attr(TRANS_DET_LANG_SUFFIX))
+                    .unwrap_or(TRANS_DET_LANG_DEFAULT)
+                    .to_owned();
+                trans.details.push(TransDetail { content, lang });
+            }
+            _ => {}
+        }
+    }
+
+    Ok(trans)
+}
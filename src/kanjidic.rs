@@ -1,125 +1,1083 @@
-use crate::errors::{ParseEnumError, ParseError, XmlError};
-use crate::radicals;
-use crate::util::{self, find_child_tag_err, get_node_attr, get_node_text};
+use crate::errors::{ParseEnumError, ParseError, ParseWarning, ValidationError};
+use crate::radicals::{self, Radicals};
+use crate::util::{
+    self, find_child_tag, find_child_tag_err, get_node_attr, get_node_attr_opt, get_node_text,
+    get_node_text_opt, missing_tag,
+};
 use roxmltree::{Document, Node};
-
-#[derive(Debug)]
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+use std::ops::{Range, RangeInclusive};
+use std::str::FromStr;
+use std::sync::Arc;
+
+mod anki;
+#[cfg(feature = "compact")]
+mod compact;
+#[cfg(feature = "csv")]
+mod csv;
+mod difficulty;
+#[cfg(feature = "fuzzy")]
+mod fuzzy;
+mod index;
+#[cfg(feature = "serde")]
+mod json;
+#[cfg(feature = "legacy")]
+mod legacy;
+mod query;
+mod romaji;
+#[cfg(feature = "streaming")]
+mod streaming;
+mod xml;
+
+pub use self::anki::AnkiField;
+#[cfg(feature = "compact")]
+pub use self::compact::{CompactEntry, CompactKanjidic, CompactMeaning};
+#[cfg(feature = "csv")]
+pub use self::csv::Column;
+pub use self::difficulty::DifficultyWeights;
+pub use self::index::{LiteralIndex, ReadingIndex};
+pub use self::query::Query;
+pub use self::romaji::RomajiSystem;
+
+/// The parsed dictionary. It owns all of its data, so it is `Send` and `Sync`
+/// and can be shared between threads behind an `Arc`, e.g. by a lookup server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct Kanjidic {
+    pub header: Header,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct Header {
     pub file_version: u32,
+    /// The version of the database the file was generated from, e.g. "2020-153"
+    /// for the 153rd day of 2020.
     pub database_version: String,
+    /// Kept as in the file, normally YYYY-MM-DD; see `Header::date_of_creation`.
     pub creation_date: String,
-
-    pub entries: Vec<Entry>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct Entry {
     pub literal: String,
     pub codepoints: Vec<Codepoint>,
 
+    #[cfg_attr(feature = "serde", serde(rename = "readingMeaning"))]
     pub reading_meanings: Vec<ReadingMeaning>,
+    #[cfg_attr(feature = "serde", serde(rename = "nanori"))]
     pub nanori_readings: Vec<String>,
 
     pub radicals: Vec<Radical>,
     pub stroke_count: u32,
     pub stroke_miscounts: Vec<u32>,
 
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub grade: Option<Grade>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub freq: Option<u32>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "jlpt", skip_serializing_if = "Option::is_none")
+    )]
     pub old_jlpt: Option<u32>,
+    pub variants: Vec<Variant>,
+    /// The names of the kanji as a radical, only present for kanji that are
+    /// radicals themselves.
+    pub rad_names: Vec<String>,
     pub dic_refs: Vec<DicRef>,
+    /// The component radicals of the kanji, which kanjidic2 doesn't have. Empty
+    /// unless filled in from a KRADFILE with `Kanjidic::attach_kradfile`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub components: Vec<char>,
+}
+
+/// A cross-reference to a variant of the kanji, such as its old form. The type
+/// is the coding of the value, e.g. "jis208" with a kuten code or "nelson_c"
+/// with a dictionary index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct Variant {
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub typ: String,
+    pub value: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct Codepoint {
     pub standard: String,
     pub value: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct ReadingMeaning {
     pub readings: Vec<Reading>,
     pub meanings: Vec<Meaning>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct Reading {
     pub value: String,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
     pub typ: ReadingType,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ReadingType {
+    #[cfg_attr(feature = "serde", serde(rename = "pinyin"))]
     Pinyin,
+    #[cfg_attr(feature = "serde", serde(rename = "korean_r"))]
     KoreanR,
+    #[cfg_attr(feature = "serde", serde(rename = "korean_h"))]
     KoreanH,
+    #[cfg_attr(feature = "serde", serde(rename = "vietnam"))]
     Vietnam,
-    Onyomi(bool, OnyomiType),
-    Kunyomi(bool),
+    #[cfg_attr(feature = "serde", serde(rename = "ja_on"))]
+    Onyomi(Option<ReadingStatus>, OnyomiType),
+    #[cfg_attr(feature = "serde", serde(rename = "ja_kun"))]
+    Kunyomi(Option<ReadingStatus>),
+    /// An r_type not known to this crate, kept only in lenient parsing.
+    #[cfg_attr(feature = "serde", serde(rename = "other"))]
+    Other(String),
+}
+
+/// The r_status of a Japanese reading. The file currently only uses "jy" to
+/// mark readings approved for jouyou use.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ReadingStatus {
+    #[cfg_attr(feature = "serde", serde(rename = "jy"))]
+    Jouyou,
+    #[cfg_attr(feature = "serde", serde(rename = "other"))]
+    Other(String),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum OnyomiType {
+    #[cfg_attr(feature = "serde", serde(rename = "kan"))]
     Kan,
+    #[cfg_attr(feature = "serde", serde(rename = "go"))]
     Go,
+    #[cfg_attr(feature = "serde", serde(rename = "tou"))]
     Tou,
+    #[cfg_attr(feature = "serde", serde(rename = "kan'you"))]
     Kanyou,
+    #[cfg_attr(feature = "serde", serde(rename = "none"))]
     None,
+    /// An on_type not known to this crate, kept only in lenient parsing.
+    #[cfg_attr(feature = "serde", serde(rename = "other"))]
+    Other(String),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct Meaning {
     pub content: String,
-    pub language: String,
+    /// Languages are interned when parsing, so all meanings in a language
+    /// share one allocation.
+    pub language: Arc<str>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct Radical {
     pub classification: RadicalType,
+    /// Both classifications index into the same 214 Kangxi radicals; they
+    /// differ only in which radical a kanji is filed under, not in the
+    /// numbering itself.
+    pub number: u32,
+    /// The radical itself, or empty if the number is outside of the 214
+    /// radicals.
     pub value: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum RadicalType {
+    #[cfg_attr(feature = "serde", serde(rename = "classical"))]
     Classical,
+    #[cfg_attr(feature = "serde", serde(rename = "nelson_c"))]
     NelsonC,
 }
 
-#[derive(Debug)]
+/// Grades are ordered by learning progression: the Kyouiku grades in order,
+/// then the rest of the Jouyou kanji, then Jinmeiyou, then the Jinmeiyou kanji
+/// that are variants of Jouyou kanji.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Grade {
+    #[cfg_attr(feature = "serde", serde(rename = "kyouiku"))]
     Kyouiku(u32),
+    #[cfg_attr(feature = "serde", serde(rename = "jouyou"))]
     Jouyou,
+    #[cfg_attr(feature = "serde", serde(rename = "jinmeiyou"))]
     Jinmeiyou,
+    #[cfg_attr(feature = "serde", serde(rename = "jouyouVariant"))]
     JouyouVariant,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DicRef {
+    /// Modern Reader's Japanese-English Character Dictionary, edited by Andrew
+    /// Nelson, now published as the Classic Nelson.
+    #[cfg_attr(feature = "serde", serde(rename = "nelson_c"))]
     NelsonC(String),
+    /// The New Nelson Japanese-English Character Dictionary, edited by John
+    /// Haig.
+    #[cfg_attr(feature = "serde", serde(rename = "nelson_n"))]
     NelsonN(String),
+    /// New Japanese-English Character Dictionary, edited by Jack Halpern.
+    #[cfg_attr(feature = "serde", serde(rename = "halpern_njecd"))]
     HalpernNJECD(String),
+    /// Kodansha Kanji Dictionary, the second edition of the NJECD, edited by
+    /// Jack Halpern.
+    #[cfg_attr(feature = "serde", serde(rename = "halpern_kkd"))]
     HalpernKKD(String),
+    /// Kanji Learners Dictionary (Kodansha), edited by Jack Halpern.
+    #[cfg_attr(feature = "serde", serde(rename = "halpern_kkld"))]
     HalpernKKLD(String),
+    /// Kanji Learners Dictionary (Kodansha), second edition (2013), edited by
+    /// Jack Halpern.
+    #[cfg_attr(feature = "serde", serde(rename = "halpern_kkld_2ed"))]
     HalpernKKLD2(String),
+    /// Remembering The Kanji, by James Heisig.
+    #[cfg_attr(feature = "serde", serde(rename = "heisig"))]
     Heisig(String),
+    /// Remembering The Kanji, sixth edition, by James Heisig.
+    #[cfg_attr(feature = "serde", serde(rename = "heisig6"))]
     Heisig6(String),
+    /// A New Dictionary of Kanji Usage (Gakken).
+    #[cfg_attr(feature = "serde", serde(rename = "gakken"))]
     Gakken(String),
+    /// Japanese Names, by P.G. O'Neill.
+    #[cfg_attr(feature = "serde", serde(rename = "oneill_names"))]
     OneillNames(String),
+    /// Essential Kanji, by P.G. O'Neill.
+    #[cfg_attr(feature = "serde", serde(rename = "oneill_kk"))]
     OneillKK(String),
-    NeillKK(String),
-    Moro(String, Option<u32>, Option<u32>),
+    /// Dai Kanwa Jiten, compiled by Morohashi.
+    #[cfg_attr(feature = "serde", serde(rename = "moro"))]
+    Moro(MoroRef),
+    /// A Guide To Remembering Japanese Characters, by Kenneth G. Henshall.
+    #[cfg_attr(feature = "serde", serde(rename = "henshall"))]
     Henshall(String),
+    /// Kanji and Kana, by Spahn and Hadamitzky.
+    #[cfg_attr(feature = "serde", serde(rename = "sh_kk"))]
     SHKK(String),
+    /// Kanji and Kana, by Spahn and Hadamitzky, 2011 edition.
+    #[cfg_attr(feature = "serde", serde(rename = "sh_kk2"))]
     SHKK2(String),
+    /// A Guide To Reading and Writing Japanese, edited by Florence Sakade.
+    #[cfg_attr(feature = "serde", serde(rename = "sakade"))]
     Sakade(String),
+    /// Japanese Kanji Flashcards, by Max Hodges and Tomoko Okazaki (series 1).
+    #[cfg_attr(feature = "serde", serde(rename = "jf_cards"))]
     JFCards(String),
+    /// A Guide To Reading and Writing Japanese, third edition, edited by
+    /// Henshall, Seeley and De Groot.
+    #[cfg_attr(feature = "serde", serde(rename = "henshall3"))]
     Henshall3(String),
+    /// Tuttle Kanji Cards, compiled by Alexander Kask.
+    #[cfg_attr(feature = "serde", serde(rename = "tutt_cards"))]
     TuttCards(String),
+    /// The Kanji Way to Japanese Language Power, by Dale Crowley.
+    #[cfg_attr(feature = "serde", serde(rename = "crowley"))]
     Crowley(String),
+    /// Kanji in Context, by Nishiguchi and Kono.
+    #[cfg_attr(feature = "serde", serde(rename = "kanji_in_context"))]
     InContext(String),
+    /// Japanese For Busy People, volumes I-III (AJLT). The value is the volume
+    /// and chapter.
+    #[cfg_attr(feature = "serde", serde(rename = "busy_people"))]
     BusyPeople(String),
+    /// The Kodansha Compact Kanji Guide.
+    #[cfg_attr(feature = "serde", serde(rename = "kodansha_compact"))]
     KodanshaCompact(String),
+    /// Les Kanjis dans la tête, Yves Maniette's French adaptation of Heisig.
+    #[cfg_attr(feature = "serde", serde(rename = "maniette"))]
     Maniette(String),
+    /// A dr_type not known to this crate and its value, kept only in lenient
+    /// parsing.
+    #[cfg_attr(feature = "serde", serde(rename = "other"))]
+    Other(String, String),
+}
+
+/// The dr_type of a dictionary reference, without its value. There is one kind
+/// for each dr_type in the kanjidic2 DTD.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DicRefKind {
+    NelsonC,
+    NelsonN,
+    HalpernNJECD,
+    HalpernKKD,
+    HalpernKKLD,
+    HalpernKKLD2,
+    Heisig,
+    Heisig6,
+    Gakken,
+    OneillNames,
+    OneillKK,
+    Moro,
+    Henshall,
+    SHKK,
+    SHKK2,
+    Sakade,
+    JFCards,
+    Henshall3,
+    TuttCards,
+    Crowley,
+    InContext,
+    BusyPeople,
+    KodanshaCompact,
+    Maniette,
+}
+
+/// A reference into Morohashi's Dai Kanwa Jiten.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct MoroRef {
+    pub index: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub volume: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub page: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct KanjidicStats {
+    pub total_entries: usize,
+    pub with_freq: usize,
+    /// Keyed by the numeric grade value, see `Grade::number`.
+    pub by_grade: HashMap<u32, usize>,
+    pub by_stroke_count: BTreeMap<u32, usize>,
+    pub jlpt_distribution: BTreeMap<u32, usize>,
+}
+
+/// The literals of the entries that differ between two dictionaries, see
+/// `Kanjidic::diff`.
+#[derive(Debug, Clone, Default)]
+pub struct KanjidicDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// The language of meanings without an m_lang attribute.
+    pub default_meaning_lang: String,
+    /// See `Kanjidic::from_file_lenient`.
+    pub lenient: bool,
+    /// Reports the name of each element this crate doesn't know about, once,
+    /// as a warning. Such elements are otherwise skipped silently.
+    pub report_unknown_tags: bool,
+    /// Lowercases the m_lang of meanings and uses '-' as the subtag separator,
+    /// so "EN" and "en" or "en_US" and "en-us" are the same language.
+    pub normalize_languages: bool,
+    /// Checks each character for the elements the DTD requires, reporting each
+    /// one missing as a warning and skipping the character, instead of failing
+    /// the parse at the first.
+    pub validate_dtd: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            default_meaning_lang: "en".to_owned(),
+            lenient: false,
+            report_unknown_tags: false,
+            normalize_languages: true,
+            validate_dtd: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum SortKey {
+    Frequency,
+    StrokeCount,
+    Grade,
+    Literal,
+}
+
+impl Entry {
+    /// Iterates over the readings of all rmgroups, in document order.
+    pub fn readings(&self) -> impl Iterator<Item = &Reading> {
+        self.reading_meanings.iter().flat_map(|rm| &rm.readings)
+    }
+
+    /// Iterates over the meanings of all rmgroups, in document order.
+    pub fn meanings(&self) -> impl Iterator<Item = &Meaning> {
+        self.reading_meanings.iter().flat_map(|rm| &rm.meanings)
+    }
+
+    /// The number of readings across all rmgroups, of every type. Nanori are
+    /// not counted.
+    pub fn reading_count(&self) -> usize {
+        self.reading_meanings
+            .iter()
+            .map(|rm| rm.readings.len())
+            .sum()
+    }
+
+    /// The number of meanings across all rmgroups, in every language.
+    pub fn meaning_count(&self) -> usize {
+        self.reading_meanings
+            .iter()
+            .map(|rm| rm.meanings.len())
+            .sum()
+    }
+
+    /// The component radicals of the kanji; see `Kanjidic::attach_kradfile`.
+    pub fn components(&self) -> &[char] {
+        &self.components
+    }
+
+    /// Returns the kanji as a char. Parsed entries are checked to have a
+    /// single-character literal, so this is only `None` if the literal has
+    /// since been emptied.
+    pub fn as_char(&self) -> Option<char> {
+        self.literal.chars().next()
+    }
+
+    /// Checks that the literal is in one of the CJK Unified Ideographs blocks,
+    /// the main block or Extensions A and B, rather than a compatibility
+    /// ideograph or symbol.
+    pub fn is_kanji(&self) -> bool {
+        self.as_char().is_some_and(util::is_kanji)
+    }
+
+    pub fn radical(&self, classification: RadicalType) -> Option<&Radical> {
+        self.radicals
+            .iter()
+            .find(|r| r.classification == classification)
+    }
+
+    pub fn classical_radical(&self) -> Option<&Radical> {
+        self.radical(RadicalType::Classical)
+    }
+
+    /// Returns the number of the radical with the classification as listed,
+    /// without the fallback of `nelson_radical`.
+    pub fn radical_number(&self, classification: RadicalType) -> Option<u32> {
+        self.radical(classification).map(|r| r.number)
+    }
+
+    /// Returns the radical Nelson files the kanji under. The file only lists a
+    /// nelson_c radical where it differs from the classical one, so this falls
+    /// back to the classical radical.
+    pub fn nelson_radical(&self) -> Option<&Radical> {
+        self.radical(RadicalType::NelsonC)
+            .or_else(|| self.classical_radical())
+    }
+
+    /// Returns the accepted stroke count followed by the common miscounts. The
+    /// first stroke_count in the file is authoritative; any others are counts
+    /// that people commonly give by mistake, for use in lookups.
+    pub fn all_stroke_counts(&self) -> Vec<u32> {
+        let mut counts = vec![self.stroke_count];
+        counts.extend(&self.stroke_miscounts);
+        counts
+    }
+
+    /// See `Grade::label`.
+    pub fn grade_label(&self) -> Option<String> {
+        self.grade.as_ref().map(Grade::label)
+    }
+
+    /// Returns labelled display values for the entry, e.g. ("Stroke count",
+    /// "8") or ("On", "コウ, ギョウ"), for rendering as a table. Fields without a
+    /// value are left out. Only the English meanings are included.
+    pub fn fields(&self) -> Vec<(String, String)> {
+        let mut fields = vec![("Stroke count", self.stroke_count.to_string())];
+        if let Some(grade) = self.grade_label() {
+            fields.push(("Grade", grade));
+        }
+        if let Some(jlpt) = self.old_jlpt {
+            fields.push(("JLPT", jlpt.to_string()));
+        }
+        if let Some(freq) = self.freq {
+            fields.push(("Frequency", freq.to_string()));
+        }
+        let lists = vec![
+            ("On", self.onyomi().join(", ")),
+            ("Kun", self.kunyomi().join(", ")),
+            ("Nanori", self.nanori_readings.join(", ")),
+            (
+                "Meanings",
+                self.english_meanings().collect::<Vec<_>>().join("; "),
+            ),
+        ];
+        fields.extend(lists.into_iter().filter(|(_, v)| !v.is_empty()));
+        fields.into_iter().map(|(k, v)| (k.to_owned(), v)).collect()
+    }
+
+    /// Returns the level in the old four-level JLPT, used until 2010, where 4
+    /// is the most elementary. This does not correspond to the N1-N5 levels of
+    /// the current test.
+    pub fn jlpt_level(&self) -> Option<u32> {
+        self.old_jlpt
+    }
+
+    /// Returns the value of the codepoint in the standard, e.g. "4e9c" for the
+    /// ucs codepoint of 亜 or "1-16-01" for its jis208 one.
+    pub fn codepoint(&self, standard: &str) -> Option<&str> {
+        self.codepoints
+            .iter()
+            .find(|c| c.standard == standard)
+            .map(|c| c.value.as_str())
+    }
+
+    /// Returns the ucs codepoint as written in the file, e.g. "4e9c"; a
+    /// shortcut for `codepoint("ucs")`.
+    pub fn ucs(&self) -> Option<&str> {
+        self.codepoint("ucs")
+    }
+
+    /// Returns the Unicode scalar value parsed from the ucs codepoint, if
+    /// present.
+    pub fn ucs_value(&self) -> Option<u32> {
+        self.codepoint("ucs")
+            .and_then(|value| u32::from_str_radix(value, 16).ok())
+    }
+
+    /// Checks for a codepoint in the standard, e.g. "jis208" or "ucs".
+    pub fn has_codepoint(&self, standard: &str) -> bool {
+        self.codepoints.iter().any(|c| c.standard == standard)
+    }
+
+    /// Compares the entries ignoring the order of their lists, such as the
+    /// readings, meanings, and dictionary references. Readings and meanings
+    /// are compared regardless of the rmgroup they are in.
+    pub fn content_eq(&self, other: &Entry) -> bool {
+        fn set<'a, T: Eq + Hash + 'a>(items: impl IntoIterator<Item = &'a T>) -> HashSet<&'a T> {
+            items.into_iter().collect()
+        }
+
+        self.literal == other.literal
+            && self.stroke_count == other.stroke_count
+            && self.grade == other.grade
+            && self.freq == other.freq
+            && self.old_jlpt == other.old_jlpt
+            && set(&self.codepoints) == set(&other.codepoints)
+            && set(&self.radicals) == set(&other.radicals)
+            && set(&self.stroke_miscounts) == set(&other.stroke_miscounts)
+            && set(&self.variants) == set(&other.variants)
+            && set(&self.rad_names) == set(&other.rad_names)
+            && set(&self.nanori_readings) == set(&other.nanori_readings)
+            && set(&self.dic_refs) == set(&other.dic_refs)
+            && set(self.readings()) == set(other.readings())
+            && set(self.meanings()) == set(other.meanings())
+    }
+
+    /// Adds what the other entry has that this one doesn't: readings,
+    /// meanings, dictionary references, and the other lists are combined
+    /// without duplicates, and missing values such as the grade or frequency
+    /// are taken from the other entry. Values present in both are kept from
+    /// this entry, except that a zero stroke count is replaced. New readings
+    /// and meanings are added in rmgroups of their own, following the
+    /// grouping of the other entry. The literals are not compared.
+    pub fn merge_from(&mut self, other: &Entry) {
+        fn union<T: Clone + PartialEq>(into: &mut Vec<T>, from: &[T]) {
+            for item in from {
+                if !into.contains(item) {
+                    into.push(item.clone());
+                }
+            }
+        }
+
+        for rm in &other.reading_meanings {
+            let readings: Vec<_> = rm
+                .readings
+                .iter()
+                .filter(|r| !self.readings().any(|own| own == *r))
+                .cloned()
+                .collect();
+            let meanings: Vec<_> = rm
+                .meanings
+                .iter()
+                .filter(|m| !self.meanings().any(|own| own == *m))
+                .cloned()
+                .collect();
+            if !readings.is_empty() || !meanings.is_empty() {
+                self.reading_meanings
+                    .push(ReadingMeaning { readings, meanings });
+            }
+        }
+        union(&mut self.nanori_readings, &other.nanori_readings);
+        union(&mut self.codepoints, &other.codepoints);
+        union(&mut self.radicals, &other.radicals);
+        union(&mut self.variants, &other.variants);
+        union(&mut self.rad_names, &other.rad_names);
+        union(&mut self.dic_refs, &other.dic_refs);
+
+        if self.stroke_count == 0 {
+            self.stroke_count = other.stroke_count;
+        }
+        union(&mut self.stroke_miscounts, &other.stroke_miscounts);
+        let stroke_count = self.stroke_count;
+        self.stroke_miscounts.retain(|&c| c != stroke_count);
+        self.grade = self.grade.or(other.grade);
+        self.freq = self.freq.or(other.freq);
+        self.old_jlpt = self.old_jlpt.or(other.old_jlpt);
+        if self.components.is_empty() {
+            self.components = other.components.clone();
+        }
+    }
+
+    pub fn moro(&self) -> Option<&MoroRef> {
+        self.dic_refs.iter().find_map(DicRef::as_moro)
+    }
+
+    /// Returns the on readings in document order, rmgroup by rmgroup. Within an
+    /// rmgroup the readings are listed most common first, and this order is
+    /// kept, so the first reading is the primary one.
+    pub fn onyomi(&self) -> Vec<&str> {
+        self.readings()
+            .filter(|r| matches!(r.typ, ReadingType::Onyomi(..)))
+            .map(|r| r.value.as_str())
+            .collect()
+    }
+
+    /// Groups the on readings by their on_type, in document order within
+    /// each group. Readings without an on_type are under `OnyomiType::None`.
+    pub fn onyomi_by_type(&self) -> HashMap<OnyomiType, Vec<&str>> {
+        let mut groups: HashMap<OnyomiType, Vec<&str>> = HashMap::new();
+        for r in self.readings() {
+            if let ReadingType::Onyomi(_, ref typ) = r.typ {
+                groups.entry(typ.clone()).or_default().push(&r.value);
+            }
+        }
+        groups
+    }
+
+    /// Returns the kun readings in document order, like `onyomi`.
+    pub fn kunyomi(&self) -> Vec<&str> {
+        self.readings()
+            .filter(|r| matches!(r.typ, ReadingType::Kunyomi(..)))
+            .map(|r| r.value.as_str())
+            .collect()
+    }
+
+    /// The first on reading, normally the most common one.
+    pub fn primary_onyomi(&self) -> Option<&str> {
+        self.readings()
+            .find(|r| matches!(r.typ, ReadingType::Onyomi(..)))
+            .map(|r| r.value.as_str())
+    }
+
+    /// The first kun reading, normally the most common one.
+    pub fn primary_kunyomi(&self) -> Option<&str> {
+        self.readings()
+            .find(|r| matches!(r.typ, ReadingType::Kunyomi(..)))
+            .map(|r| r.value.as_str())
+    }
+
+    /// Like `onyomi`, but only the readings marked as approved for jōyō use;
+    /// see `Reading::is_jouyou_approved`.
+    pub fn jouyou_onyomi(&self) -> Vec<&str> {
+        self.readings()
+            .filter(|r| matches!(r.typ, ReadingType::Onyomi(..)) && r.is_jouyou_approved())
+            .map(|r| r.value.as_str())
+            .collect()
+    }
+
+    /// Like `kunyomi`, but only the readings marked as approved for jōyō use.
+    pub fn jouyou_kunyomi(&self) -> Vec<&str> {
+        self.readings()
+            .filter(|r| matches!(r.typ, ReadingType::Kunyomi(..)) && r.is_jouyou_approved())
+            .map(|r| r.value.as_str())
+            .collect()
+    }
+
+    /// Like `onyomi`, but readings repeated across rmgroups are only returned
+    /// once, at their first position.
+    pub fn unique_onyomi(&self) -> Vec<&str> {
+        dedup_readings(self.onyomi())
+    }
+
+    /// Like `kunyomi`, but readings repeated across rmgroups are only returned
+    /// once, at their first position.
+    pub fn unique_kunyomi(&self) -> Vec<&str> {
+        dedup_readings(self.kunyomi())
+    }
+
+    /// The first English meaning of the kanji.
+    pub fn primary_meaning(&self) -> Option<&str> {
+        self.english_meanings().next()
+    }
+
+    fn english_meanings(&self) -> impl Iterator<Item = &str> {
+        self.meanings()
+            .filter(|m| &*m.language == "en")
+            .map(|m| m.content.as_str())
+    }
+}
+
+fn dedup_readings(readings: Vec<&str>) -> Vec<&str> {
+    let mut seen = HashSet::new();
+    readings.into_iter().filter(|r| seen.insert(*r)).collect()
+}
+
+const DISPLAY_MEANINGS: usize = 5;
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.literal)?;
+        writeln!(f, "Strokes: {}", self.stroke_count)?;
+        if let Some(grade) = self.grade_label() {
+            writeln!(f, "Grade: {}", grade)?;
+        }
+
+        let onyomi = self.onyomi();
+        if !onyomi.is_empty() {
+            writeln!(f, "On: {}", onyomi.join(", "))?;
+        }
+        let kunyomi = self.kunyomi();
+        if !kunyomi.is_empty() {
+            writeln!(f, "Kun: {}", kunyomi.join(", "))?;
+        }
+
+        let meanings: Vec<_> = self.english_meanings().take(DISPLAY_MEANINGS).collect();
+        write!(f, "Meanings: {}", meanings.join("; "))
+    }
+}
+
+impl Meaning {
+    /// Checks that the meaning is only printable ASCII, i.e. without control
+    /// characters or anything outside ASCII. Meanings in languages other than
+    /// English often aren't.
+    pub fn is_ascii_printable(&self) -> bool {
+        self.content.chars().all(|c| matches!(c, ' '..='~'))
+    }
+
+    /// Checks for control characters or the U+FFFD replacement character,
+    /// which are signs of a badly decoded or garbled file.
+    pub fn is_suspicious(&self) -> bool {
+        self.content
+            .chars()
+            .any(|c| c.is_control() || c == char::REPLACEMENT_CHARACTER)
+    }
+}
+
+impl Reading {
+    /// The reading with any katakana converted to hiragana, so that on and kun
+    /// readings can be compared against the same input.
+    pub fn normalized_kana(&self) -> String {
+        util::to_hiragana(&self.value)
+    }
+
+    /// Compares the reading to the input as kana, ignoring the okurigana
+    /// separator and affix markers on both sides and treating katakana and
+    /// hiragana alike, so "たべる" matches the kun reading "た.べる" and "ほど"
+    /// matches "-ほど".
+    pub fn matches_kana(&self, input: &str) -> bool {
+        strip_reading_markers(&self.normalized_kana())
+            == strip_reading_markers(&util::to_hiragana(input))
+    }
+
+    /// The part of the reading before the okurigana separator, without any
+    /// affix markers. For readings without okurigana this is the whole reading.
+    pub fn stem(&self) -> &str {
+        let value = self.value.trim_matches('-');
+        match value.find('.') {
+            Some(i) => &value[..i],
+            None => value,
+        }
+    }
+
+    /// The okurigana following the `.` separator, if the reading has any.
+    pub fn okurigana(&self) -> Option<&str> {
+        let value = self.value.trim_matches('-');
+        value.find('.').map(|i| &value[i + 1..])
+    }
+
+    pub fn status(&self) -> Option<&ReadingStatus> {
+        match self.typ {
+            ReadingType::Onyomi(ref status, _) | ReadingType::Kunyomi(ref status) => {
+                status.as_ref()
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_jouyou_approved(&self) -> bool {
+        matches!(self.status(), Some(ReadingStatus::Jouyou))
+    }
+
+    /// Whether the reading is only used as a prefix, marked by a trailing `-`.
+    pub fn is_prefix(&self) -> bool {
+        self.value.ends_with('-')
+    }
+
+    /// Whether the reading is only used as a suffix, marked by a leading `-`.
+    pub fn is_suffix(&self) -> bool {
+        self.value.starts_with('-')
+    }
+}
+
+impl DicRefKind {
+    pub const ALL: [DicRefKind; 24] = [
+        DicRefKind::NelsonC,
+        DicRefKind::NelsonN,
+        DicRefKind::HalpernNJECD,
+        DicRefKind::HalpernKKD,
+        DicRefKind::HalpernKKLD,
+        DicRefKind::HalpernKKLD2,
+        DicRefKind::Heisig,
+        DicRefKind::Heisig6,
+        DicRefKind::Gakken,
+        DicRefKind::OneillNames,
+        DicRefKind::OneillKK,
+        DicRefKind::Moro,
+        DicRefKind::Henshall,
+        DicRefKind::SHKK,
+        DicRefKind::SHKK2,
+        DicRefKind::Sakade,
+        DicRefKind::JFCards,
+        DicRefKind::Henshall3,
+        DicRefKind::TuttCards,
+        DicRefKind::Crowley,
+        DicRefKind::InContext,
+        DicRefKind::BusyPeople,
+        DicRefKind::KodanshaCompact,
+        DicRefKind::Maniette,
+    ];
+
+    /// The dr_type attribute value of the kind.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            DicRefKind::NelsonC => "nelson_c",
+            DicRefKind::NelsonN => "nelson_n",
+            DicRefKind::HalpernNJECD => "halpern_njecd",
+            DicRefKind::HalpernKKD => "halpern_kkd",
+            DicRefKind::HalpernKKLD => "halpern_kkld",
+            DicRefKind::HalpernKKLD2 => "halpern_kkld_2ed",
+            DicRefKind::Heisig => "heisig",
+            DicRefKind::Heisig6 => "heisig6",
+            DicRefKind::Gakken => "gakken",
+            DicRefKind::OneillNames => "oneill_names",
+            DicRefKind::OneillKK => "oneill_kk",
+            DicRefKind::Moro => "moro",
+            DicRefKind::Henshall => "henshall",
+            DicRefKind::SHKK => "sh_kk",
+            DicRefKind::SHKK2 => "sh_kk2",
+            DicRefKind::Sakade => "sakade",
+            DicRefKind::JFCards => "jf_cards",
+            DicRefKind::Henshall3 => "henshall3",
+            DicRefKind::TuttCards => "tutt_cards",
+            DicRefKind::Crowley => "crowley",
+            DicRefKind::InContext => "kanji_in_context",
+            DicRefKind::BusyPeople => "busy_people",
+            DicRefKind::KodanshaCompact => "kodansha_compact",
+            DicRefKind::Maniette => "maniette",
+        }
+    }
+
+    /// The dr_type attribute values of all kinds, in the order of `ALL`.
+    pub fn all_types() -> Vec<&'static str> {
+        DicRefKind::ALL.iter().map(DicRefKind::as_str).collect()
+    }
+}
+
+impl FromStr for DicRefKind {
+    type Err = ParseEnumError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DicRefKind::ALL
+            .iter()
+            .copied()
+            .find(|k| k.as_str() == s)
+            .ok_or_else(|| ParseEnumError::new(s, DicRefKind::all_types()))
+    }
+}
+
+impl DicRef {
+    /// Returns the kind of the reference, or `None` for references of an
+    /// unknown dr_type.
+    pub fn kind(&self) -> Option<DicRefKind> {
+        let kind = match *self {
+            DicRef::NelsonC(_) => DicRefKind::NelsonC,
+            DicRef::NelsonN(_) => DicRefKind::NelsonN,
+            DicRef::HalpernNJECD(_) => DicRefKind::HalpernNJECD,
+            DicRef::HalpernKKD(_) => DicRefKind::HalpernKKD,
+            DicRef::HalpernKKLD(_) => DicRefKind::HalpernKKLD,
+            DicRef::HalpernKKLD2(_) => DicRefKind::HalpernKKLD2,
+            DicRef::Heisig(_) => DicRefKind::Heisig,
+            DicRef::Heisig6(_) => DicRefKind::Heisig6,
+            DicRef::Gakken(_) => DicRefKind::Gakken,
+            DicRef::OneillNames(_) => DicRefKind::OneillNames,
+            DicRef::OneillKK(_) => DicRefKind::OneillKK,
+            DicRef::Moro(_) => DicRefKind::Moro,
+            DicRef::Henshall(_) => DicRefKind::Henshall,
+            DicRef::SHKK(_) => DicRefKind::SHKK,
+            DicRef::SHKK2(_) => DicRefKind::SHKK2,
+            DicRef::Sakade(_) => DicRefKind::Sakade,
+            DicRef::JFCards(_) => DicRefKind::JFCards,
+            DicRef::Henshall3(_) => DicRefKind::Henshall3,
+            DicRef::TuttCards(_) => DicRefKind::TuttCards,
+            DicRef::Crowley(_) => DicRefKind::Crowley,
+            DicRef::InContext(_) => DicRefKind::InContext,
+            DicRef::BusyPeople(_) => DicRefKind::BusyPeople,
+            DicRef::KodanshaCompact(_) => DicRefKind::KodanshaCompact,
+            DicRef::Maniette(_) => DicRefKind::Maniette,
+            DicRef::Other(..) => return None,
+        };
+        Some(kind)
+    }
+
+    /// Returns the reference number, which for Morohashi is the index.
+    pub fn value(&self) -> &str {
+        match *self {
+            DicRef::NelsonC(ref v)
+            | DicRef::NelsonN(ref v)
+            | DicRef::HalpernNJECD(ref v)
+            | DicRef::HalpernKKD(ref v)
+            | DicRef::HalpernKKLD(ref v)
+            | DicRef::HalpernKKLD2(ref v)
+            | DicRef::Heisig(ref v)
+            | DicRef::Heisig6(ref v)
+            | DicRef::Gakken(ref v)
+            | DicRef::OneillNames(ref v)
+            | DicRef::OneillKK(ref v)
+            | DicRef::Henshall(ref v)
+            | DicRef::SHKK(ref v)
+            | DicRef::SHKK2(ref v)
+            | DicRef::Sakade(ref v)
+            | DicRef::JFCards(ref v)
+            | DicRef::Henshall3(ref v)
+            | DicRef::TuttCards(ref v)
+            | DicRef::Crowley(ref v)
+            | DicRef::InContext(ref v)
+            | DicRef::BusyPeople(ref v)
+            | DicRef::KodanshaCompact(ref v)
+            | DicRef::Maniette(ref v)
+            | DicRef::Other(_, ref v) => v,
+            DicRef::Moro(ref moro) => &moro.index,
+        }
+    }
+
+    pub fn as_moro(&self) -> Option<&MoroRef> {
+        match *self {
+            DicRef::Moro(ref moro) => Some(moro),
+            _ => None,
+        }
+    }
+}
+
+impl Grade {
+    fn rank(&self) -> (u8, u32) {
+        match *self {
+            Grade::Kyouiku(i) => (0, i),
+            Grade::Jouyou => (1, 0),
+            Grade::Jinmeiyou => (2, 0),
+            Grade::JouyouVariant => (3, 0),
+        }
+    }
+}
+
+impl Ord for Grade {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+impl PartialOrd for Grade {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Header {
+    // The database version as (year, day of year), e.g. (2020, 153).
+    fn version_number(&self) -> Option<(u32, u32)> {
+        let mut parts = self.database_version.splitn(2, '-');
+        let year = parts.next()?.trim().parse().ok()?;
+        let day = parts.next()?.trim().parse().ok()?;
+        Some((year, day))
+    }
+
+    // The creation date as (year, month, day), with no calendar checks.
+    fn creation_ymd(&self) -> Option<(u32, u32, u32)> {
+        let mut parts = self.creation_date.splitn(3, '-');
+        let year = parts.next()?.trim().parse().ok()?;
+        let month = parts.next()?.trim().parse().ok()?;
+        let day = parts.next()?.trim().parse().ok()?;
+        Some((year, month, day))
+    }
+
+    /// Parses the creation date, returning `None` if it isn't in the YYYY-MM-DD
+    /// format; the raw value is still available in `creation_date`.
+    #[cfg(feature = "chrono")]
+    pub fn date_of_creation(&self) -> Option<chrono::NaiveDate> {
+        let mut parts = self.creation_date.splitn(3, '-');
+        let mut next = |len: usize| {
+            parts
+                .next()
+                .filter(|p| p.len() == len && p.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|p| p.parse().ok())
+        };
+        let (year, month, day) = (next(4)?, next(2)?, next(2)?);
+        chrono::NaiveDate::from_ymd_opt(year as i32, month, day)
+    }
+}
+
+impl Grade {
+    /// The numeric value of the grade as it appears in the kanjidic2 file.
+    pub fn number(&self) -> u32 {
+        match *self {
+            Grade::Kyouiku(i) => i,
+            Grade::Jouyou => 8,
+            Grade::Jinmeiyou => 9,
+            Grade::JouyouVariant => 10,
+        }
+    }
+
+    /// A name for the grade for display, e.g. "Grade 3 (Kyōiku)" or "Jōyō".
+    pub fn label(&self) -> String {
+        match *self {
+            Grade::Kyouiku(i) => format!("Grade {} (Kyōiku)", i),
+            Grade::Jouyou => "Jōyō".to_owned(),
+            Grade::Jinmeiyou => "Jinmeiyō".to_owned(),
+            Grade::JouyouVariant => "Jinmeiyō (Jōyō variant)".to_owned(),
+        }
+    }
+}
+
+impl Radical {
+    pub fn is_classical(&self) -> bool {
+        self.classification == RadicalType::Classical
+    }
+
+    pub fn is_nelson(&self) -> bool {
+        self.classification == RadicalType::NelsonC
+    }
 }
 
 impl Kanjidic {
@@ -127,6 +1085,200 @@ impl Kanjidic {
         self.entries.iter().find(|e| e.literal == literal)
     }
 
+    /// The position of the entry in `entries`, for use with `entry`. The
+    /// position stays valid as long as entries aren't added or removed before
+    /// it.
+    pub fn index_of(&self, literal: &str) -> Option<usize> {
+        self.entries.iter().position(|e| e.literal == literal)
+    }
+
+    pub fn entry(&self, index: usize) -> Option<&Entry> {
+        self.entries.get(index)
+    }
+
+    /// The entries as a mutable slice, for correcting them in place. Entries
+    /// can't be added or removed through it, so positions from `index_of` stay
+    /// valid.
+    pub fn entries_mut(&mut self) -> &mut [Entry] {
+        &mut self.entries
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Entry> {
+        self.entries.iter_mut()
+    }
+
+    /// Like `find_literal`, but returns a copy of the entry that doesn't borrow
+    /// the dictionary, e.g. for handing across an FFI boundary.
+    pub fn find_literal_owned(&self, literal: &str) -> Option<Entry> {
+        self.find_literal(literal).cloned()
+    }
+
+    /// Looks up the character, which may be outside the BMP, like the kanji of
+    /// the supplementary ideographic plane.
+    pub fn find_char(&self, c: char) -> Option<&Entry> {
+        let mut buf = [0; 4];
+        self.find_literal(c.encode_utf8(&mut buf))
+    }
+
+    /// Looks up each of the literals, returning the results in the same order.
+    /// An index of the whole dictionary is built on each call; for many
+    /// separate lookups, build a `LiteralIndex` once instead.
+    pub fn find_literals(&self, literals: &[&str]) -> Vec<Option<&Entry>> {
+        self.build_literal_index().find_literals(literals)
+    }
+
+    /// Returns the entries for the characters in the text, in order of first
+    /// appearance and without duplicates. Characters without an entry, such as
+    /// kana and punctuation, are skipped. Like `find_literals`, this builds an
+    /// index on each call.
+    pub fn find_in_text(&self, text: &str) -> Vec<&Entry> {
+        self.subset(text.chars())
+    }
+
+    /// Returns the entries for the characters, e.g. the kanji of a font or a
+    /// curriculum, in the order given and without duplicates. Characters
+    /// without an entry are skipped. Like `find_literals`, this builds an index
+    /// on each call.
+    pub fn subset<I: IntoIterator<Item = char>>(&self, chars: I) -> Vec<&Entry> {
+        self.build_literal_index().subset(chars)
+    }
+
+    /// Lists the entries added in, removed from, or changed in the other
+    /// dictionary, matched by literal and compared with `Entry::content_eq`.
+    pub fn diff(&self, other: &Kanjidic) -> KanjidicDiff {
+        let ours = self.build_literal_index();
+        let theirs = other.build_literal_index();
+
+        let mut diff = KanjidicDiff::default();
+        for e in &self.entries {
+            match theirs.find_literal(&e.literal) {
+                Some(o) if !e.content_eq(o) => diff.changed.push(e.literal.clone()),
+                Some(_) => {}
+                None => diff.removed.push(e.literal.clone()),
+            }
+        }
+        diff.added = other
+            .entries
+            .iter()
+            .filter(|e| ours.find_literal(&e.literal).is_none())
+            .map(|e| e.literal.clone())
+            .collect();
+        diff
+    }
+
+    pub fn into_entries(self) -> Vec<Entry> {
+        self.entries
+    }
+
+    /// Adds the entries of the other dictionary. An entry whose literal is
+    /// already present replaces the existing entry in place, so the later
+    /// dictionary wins; fields are not combined. Other entries are appended in
+    /// their order. The header of this dictionary is kept.
+    pub fn merge(&mut self, other: Kanjidic) {
+        let mut positions: HashMap<String, usize> = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.literal.clone(), i))
+            .collect();
+        for e in other.entries {
+            match positions.get(&e.literal) {
+                Some(&i) => self.entries[i] = e,
+                None => {
+                    positions.insert(e.literal.clone(), self.entries.len());
+                    self.entries.push(e);
+                }
+            }
+        }
+    }
+
+    /// Sets the components of each entry to those in the KRADFILE. Entries the
+    /// file doesn't list, or with an empty literal, are left without
+    /// components.
+    pub fn attach_kradfile(&mut self, kradfile: &Radicals) {
+        for e in &mut self.entries {
+            e.components = e
+                .as_char()
+                .and_then(|c| kradfile.components(c))
+                .map(<[char]>::to_vec)
+                .unwrap_or_default();
+        }
+    }
+
+    /// Finds the entries the variants of the entry refer to. Only variants
+    /// given by codepoint, such as jis208 or ucs, can be resolved.
+    pub fn variants_of(&self, entry: &Entry) -> Vec<&Entry> {
+        entry
+            .variants
+            .iter()
+            .filter_map(|v| {
+                self.entries.iter().find(|e| {
+                    e.codepoints
+                        .iter()
+                        .any(|c| c.standard == v.typ && c.value == v.value)
+                })
+            })
+            .collect()
+    }
+
+    /// Finds the entries at the level in the old four-level JLPT; see
+    /// `Entry::jlpt_level`.
+    pub fn by_jlpt(&self, level: u32) -> Vec<&Entry> {
+        self.filter(|e| e.jlpt_level() == Some(level))
+    }
+
+    /// Counts the entries at each old JLPT level in a single pass, like the
+    /// `jlpt_distribution` of `stats`. Entries without a level aren't counted.
+    pub fn jlpt_counts(&self) -> BTreeMap<u32, usize> {
+        let mut counts = BTreeMap::new();
+        for level in self.entries.iter().filter_map(|e| e.old_jlpt) {
+            *counts.entry(level).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    pub fn with_codepoint_standard(&self, standard: &str) -> Vec<&Entry> {
+        self.filter(|e| e.has_codepoint(standard))
+    }
+
+    /// Finds the entry with the JIS code in the standard, "jis208", "jis212" or
+    /// "jis213", given as the plane and the kuten, either as "16-01" or as four
+    /// digits, "1601". The code spaces of the standards overlap, so the same
+    /// code can be a different kanji in each; the jis208 and jis212 codes in
+    /// the file are all in plane 1.
+    pub fn find_by_jis(&self, standard: &str, plane: u8, kuten: &str) -> Option<&Entry> {
+        let kuten = parse_kuten(kuten)?;
+        let code = (u32::from(plane), kuten.0, kuten.1);
+        self.entries.iter().find(|e| {
+            e.codepoints
+                .iter()
+                .any(|c| c.standard == standard && parse_jis_code(&c.value) == Some(code))
+        })
+    }
+
+    /// Finds the entry with the reference number in the dictionary. Numbers
+    /// are compared as strings, so "1234.5" only matches exactly. For repeated
+    /// lookups, build an index with `dic_ref_index` instead.
+    pub fn by_dic_ref(&self, kind: DicRefKind, number: &str) -> Option<&Entry> {
+        self.entries.iter().find(|e| {
+            e.dic_refs
+                .iter()
+                .any(|d| d.kind() == Some(kind) && d.value() == number)
+        })
+    }
+
+    /// Maps the reference numbers in the dictionary to their entries. If
+    /// several entries share a number, the first is kept.
+    pub fn dic_ref_index(&self, kind: DicRefKind) -> HashMap<&str, &Entry> {
+        let mut index = HashMap::new();
+        for e in &self.entries {
+            for d in e.dic_refs.iter().filter(|d| d.kind() == Some(kind)) {
+                index.entry(d.value()).or_insert(e);
+            }
+        }
+        index
+    }
+
     pub fn filter<F>(&self, predicate: F) -> Vec<&Entry>
     where
         F: Fn(&Entry) -> bool,
@@ -138,16 +1290,363 @@ impl Kanjidic {
     where
         F: Fn(&Meaning) -> bool,
     {
+        self.entries
+            .iter()
+            .filter(|e| e.meanings().any(&predicate))
+            .collect()
+    }
+
+    /// Like `filter_meaning`, but returns each matching meaning with its entry,
+    /// so an entry with several matching meanings appears once for each.
+    pub fn find_meanings<F>(&self, predicate: F) -> Vec<(&Entry, &Meaning)>
+    where
+        F: Fn(&Meaning) -> bool,
+    {
+        self.entries
+            .iter()
+            .flat_map(|e| e.meanings().map(move |m| (e, m)))
+            .filter(|(_, m)| predicate(m))
+            .collect()
+    }
+
+    pub fn meaning_languages(&self) -> Vec<String> {
+        let languages: BTreeSet<_> = self
+            .entries
+            .iter()
+            .flat_map(|e| &e.reading_meanings)
+            .flat_map(|rm| &rm.meanings)
+            .map(|m| &*m.language)
+            .collect();
+        languages.into_iter().map(|l| l.to_owned()).collect()
+    }
+
+    /// Returns the entries ordered by the given key, ascending. Entries without
+    /// a value for the key are placed last, and ties are broken by the literal's
+    /// codepoint so that the order is deterministic.
+    pub fn sorted_by(&self, key: SortKey) -> Vec<&Entry> {
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_by(|a, b| {
+            let ord = match key {
+                SortKey::Frequency => cmp_none_last(a.freq, b.freq),
+                SortKey::StrokeCount => a.stroke_count.cmp(&b.stroke_count),
+                SortKey::Grade => cmp_none_last(
+                    a.grade.as_ref().map(Grade::number),
+                    b.grade.as_ref().map(Grade::number),
+                ),
+                SortKey::Literal => Ordering::Equal,
+            };
+            ord.then_with(|| a.literal.cmp(&b.literal))
+        });
+        entries
+    }
+
+    /// Whether this dictionary was generated after the other, e.g. to tell if a
+    /// download is newer than a cached copy. The database versions are compared
+    /// numerically, and the creation dates are used if the versions are equal
+    /// or either one can't be parsed. Returns false if neither can be compared.
+    pub fn is_newer_than(&self, other: &Kanjidic) -> bool {
+        let (a, b) = (&self.header, &other.header);
+        let by_version = match (a.version_number(), b.version_number()) {
+            (Some(x), Some(y)) => x.cmp(&y),
+            _ => Ordering::Equal,
+        };
+        let by_date = || match (a.creation_ymd(), b.creation_ymd()) {
+            (Some(x), Some(y)) => x.cmp(&y),
+            _ => Ordering::Equal,
+        };
+        by_version.then_with(by_date) == Ordering::Greater
+    }
+
+    /// Returns every entry in an order for study: the ranked kanji by frequency,
+    /// most common first, followed by the unranked ones by stroke count. Ties
+    /// in either part are broken by the literal's codepoint.
+    pub fn study_order(&self) -> Vec<&Entry> {
+        self.sorted_by_cmp(|a, b| {
+            let ord = match (a.freq, b.freq) {
+                (Some(x), Some(y)) => x.cmp(&y),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => a.stroke_count.cmp(&b.stroke_count),
+            };
+            ord.then_with(|| a.literal.cmp(&b.literal))
+        })
+    }
+
+    /// Returns the entries ordered by the comparator, for orderings `SortKey`
+    /// doesn't cover. The sort is stable, so entries that compare equal stay in
+    /// document order.
+    pub fn sorted_by_cmp<F>(&self, mut cmp: F) -> Vec<&Entry>
+    where
+        F: FnMut(&Entry, &Entry) -> Ordering,
+    {
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_by(|a, b| cmp(a, b));
+        entries
+    }
+
+    /// Finds entries with an on, kun, or nanori reading matching the query. Both
+    /// sides are compared in hiragana with the okurigana separator and affix
+    /// markers removed, and a kun reading also matches on its stem alone, so
+    /// "た.べる" is found by both "たべる" and "た".
+    pub fn search_reading(&self, reading: &str) -> Vec<&Entry> {
+        let query = strip_reading_markers(&util::to_hiragana(reading));
         self.entries
             .iter()
             .filter(|e| {
-                e.reading_meanings
-                    .iter()
-                    .flat_map(|rm| &rm.meanings)
-                    .any(|m| predicate(&m))
+                let japanese = e
+                    .readings()
+                    .filter(|r| matches!(r.typ, ReadingType::Onyomi(..) | ReadingType::Kunyomi(..)))
+                    .map(|r| r.value.as_str());
+                japanese
+                    .chain(e.nanori_readings.iter().map(|n| n.as_str()))
+                    .any(|r| reading_matches(r, &query))
             })
             .collect()
     }
+
+    /// Finds entries with an on reading matching the query, which may be given
+    /// in hiragana or katakana.
+    pub fn search_onyomi(&self, reading: &str) -> Vec<&Entry> {
+        let query = strip_reading_markers(&util::to_katakana(reading));
+        self.filter(|e| {
+            e.onyomi()
+                .into_iter()
+                .any(|r| strip_reading_markers(r) == query)
+        })
+    }
+
+    /// Finds entries with a kun reading matching the query, compared as in
+    /// `search_reading`.
+    pub fn search_kunyomi(&self, reading: &str) -> Vec<&Entry> {
+        let query = strip_reading_markers(&util::to_hiragana(reading));
+        self.filter(|e| e.kunyomi().into_iter().any(|r| reading_matches(r, &query)))
+    }
+
+    /// Like `search_onyomi`, but sorted by frequency, most frequent first, with
+    /// the entries without a frequency rank last.
+    pub fn kanji_with_onyomi(&self, reading: &str) -> Vec<&Entry> {
+        let mut found = self.search_onyomi(reading);
+        found.sort_by(|a, b| cmp_none_last(a.freq, b.freq));
+        found
+    }
+
+    /// Like `search_kunyomi`, but sorted by frequency as in
+    /// `kanji_with_onyomi`.
+    pub fn kanji_with_kunyomi(&self, reading: &str) -> Vec<&Entry> {
+        let mut found = self.search_kunyomi(reading);
+        found.sort_by(|a, b| cmp_none_last(a.freq, b.freq));
+        found
+    }
+}
+
+impl Kanjidic {
+    /// Finds the meanings that look garbled, see `Meaning::is_suspicious`.
+    pub fn suspicious_meanings(&self) -> Vec<(&Entry, &Meaning)> {
+        self.find_meanings(Meaning::is_suspicious)
+    }
+
+    /// Checks the entries for data that parses but is unlikely to be correct,
+    /// such as a zero stroke count, a miscount equal to the actual count, no
+    /// codepoints, or a graded kanji without any Japanese readings.
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        for e in &self.entries {
+            if e.stroke_count == 0 {
+                errors.push(ValidationError::ZeroStrokeCount(e.literal.clone()));
+            }
+            for &miscount in &e.stroke_miscounts {
+                if miscount == 0 || miscount == e.stroke_count {
+                    errors.push(ValidationError::InvalidStrokeMiscount(
+                        e.literal.clone(),
+                        miscount,
+                    ));
+                }
+            }
+            if e.codepoints.is_empty() {
+                errors.push(ValidationError::MissingCodepoints(e.literal.clone()));
+            }
+            let has_japanese = e
+                .readings()
+                .any(|r| matches!(r.typ, ReadingType::Onyomi(..) | ReadingType::Kunyomi(..)));
+            if e.grade.is_some() && !has_japanese {
+                errors.push(ValidationError::MissingReadings(e.literal.clone()));
+            }
+        }
+        errors
+    }
+}
+
+impl Kanjidic {
+    pub fn stats(&self) -> KanjidicStats {
+        let mut stats = KanjidicStats {
+            total_entries: self.entries.len(),
+            with_freq: 0,
+            by_grade: HashMap::new(),
+            by_stroke_count: BTreeMap::new(),
+            jlpt_distribution: BTreeMap::new(),
+        };
+
+        for e in &self.entries {
+            if e.freq.is_some() {
+                stats.with_freq += 1;
+            }
+            if let Some(ref grade) = e.grade {
+                *stats.by_grade.entry(grade.number()).or_insert(0) += 1;
+            }
+            *stats.by_stroke_count.entry(e.stroke_count).or_insert(0) += 1;
+            if let Some(jlpt) = e.old_jlpt {
+                *stats.jlpt_distribution.entry(jlpt).or_insert(0) += 1;
+            }
+        }
+
+        stats
+    }
+
+    /// The range of the accepted stroke counts of the entries, ignoring the
+    /// miscounts. An empty dictionary gives `0..=0`.
+    pub fn stroke_count_range(&self) -> RangeInclusive<u32> {
+        let counts = self.entries.iter().map(|e| e.stroke_count);
+        let min = counts.clone().min().unwrap_or(0);
+        let max = counts.max().unwrap_or(0);
+        min..=max
+    }
+
+    /// The range of the frequency ranks of the entries that have one, or
+    /// `None` if no entry does.
+    pub fn freq_range(&self) -> Option<RangeInclusive<u32>> {
+        let freqs = self.entries.iter().filter_map(|e| e.freq);
+        Some(freqs.clone().min()?..=freqs.max()?)
+    }
+
+    /// Groups the graded entries by grade, in grade order. Entries without a
+    /// grade are left out; see `ungraded`.
+    pub fn group_by_grade(&self) -> BTreeMap<Grade, Vec<&Entry>> {
+        let mut groups: BTreeMap<Grade, Vec<&Entry>> = BTreeMap::new();
+        for e in &self.entries {
+            if let Some(grade) = e.grade {
+                groups.entry(grade).or_default().push(e);
+            }
+        }
+        groups
+    }
+
+    /// See `Entry::is_kanji`.
+    pub fn kanji_only(&self) -> Vec<&Entry> {
+        self.filter(Entry::is_kanji)
+    }
+
+    pub fn ungraded(&self) -> Vec<&Entry> {
+        self.filter(|e| e.grade.is_none())
+    }
+
+    /// Finds the entries without a meaning in the language, e.g. "en".
+    pub fn missing_meaning(&self, lang: &str) -> Vec<&Entry> {
+        self.filter(|e| e.meanings().all(|m| &*m.language != lang))
+    }
+
+    /// Finds the entries without a reading of a type the filter accepts, e.g.
+    /// `|t| matches!(t, ReadingType::Onyomi(..))` for those with no on
+    /// reading. Nanori are not readings for this purpose.
+    pub fn missing_reading<F>(&self, typ_filter: F) -> Vec<&Entry>
+    where
+        F: Fn(&ReadingType) -> bool,
+    {
+        self.filter(|e| !e.readings().any(|r| typ_filter(&r.typ)))
+    }
+
+    /// Finds the entries filed under the Kangxi radical number in the classical
+    /// classification.
+    pub fn by_radical(&self, radical_number: u32) -> Vec<&Entry> {
+        self.by_radical_in(RadicalType::Classical, radical_number)
+    }
+
+    /// Finds the entries filed under the Kangxi radical number in the given
+    /// classification. Entries without a radical of that classification never
+    /// match.
+    pub fn by_radical_in(&self, classification: RadicalType, radical_number: u32) -> Vec<&Entry> {
+        self.filter(|e| {
+            e.radicals
+                .iter()
+                .any(|r| r.classification == classification && r.number == radical_number)
+        })
+    }
+
+    /// Finds the entries whose classical radical is the character, given in its
+    /// standalone form (水 rather than 氵).
+    pub fn by_radical_char(&self, c: char) -> Vec<&Entry> {
+        self.filter(|e| {
+            e.radicals
+                .iter()
+                .any(|r| r.classification == RadicalType::Classical && r.value.chars().eq(Some(c)))
+        })
+    }
+
+    /// Finds up to `n` other entries with the same classical radical as the
+    /// literal, closest in stroke count first, for "did you mean" suggestions.
+    /// Returns nothing if the literal isn't in the dictionary or has no
+    /// classical radical.
+    pub fn similar_to(&self, literal: &str, n: usize) -> Vec<&Entry> {
+        let kanji = match self.find_literal(literal) {
+            Some(kanji) => kanji,
+            None => return Vec::new(),
+        };
+        let radical = match kanji.radical_number(RadicalType::Classical) {
+            Some(radical) => radical,
+            None => return Vec::new(),
+        };
+        let strokes = kanji.stroke_count;
+
+        let mut similar: Vec<_> = self
+            .by_radical(radical)
+            .into_iter()
+            .filter(|e| e.literal != literal)
+            .collect();
+        similar.sort_by(|a, b| {
+            let diff = |e: &Entry| (i64::from(e.stroke_count) - i64::from(strokes)).abs();
+            diff(a)
+                .cmp(&diff(b))
+                .then_with(|| a.literal.cmp(&b.literal))
+        });
+        similar.truncate(n);
+        similar
+    }
+}
+
+fn strip_reading_markers(reading: &str) -> String {
+    reading.chars().filter(|&c| c != '.' && c != '-').collect()
+}
+
+fn reading_matches(reading: &str, query: &str) -> bool {
+    let reading = util::to_hiragana(reading);
+    let stem = reading.split('.').next().unwrap_or_default();
+    strip_reading_markers(&reading) == query || strip_reading_markers(stem) == query
+}
+
+fn parse_kuten(kuten: &str) -> Option<(u32, u32)> {
+    match kuten.split_once('-') {
+        Some((ku, ten)) => Some((ku.parse().ok()?, ten.parse().ok()?)),
+        None if kuten.len() == 4 => {
+            let (ku, ten) = kuten.split_at(2);
+            Some((ku.parse().ok()?, ten.parse().ok()?))
+        }
+        None => None,
+    }
+}
+
+// The codepoint values of the JIS standards are plane-ku-ten, e.g. "1-16-01".
+fn parse_jis_code(value: &str) -> Option<(u32, u32, u32)> {
+    let (plane, kuten) = value.split_once('-')?;
+    let (ku, ten) = parse_kuten(kuten)?;
+    Some((plane.parse().ok()?, ku, ten))
+}
+
+fn cmp_none_last<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
 }
 
 const_strs!(
@@ -156,28 +1655,303 @@ const_strs!(
     CHARACTER: "character"
 );
 
+#[cfg(feature = "fs")]
 impl Kanjidic {
     pub fn from_file(filepath: &str) -> Result<Self, ParseError> {
         let contents = util::read_file(filepath)?;
-        let doc = Document::parse(&contents).map_err(XmlError::Roxml)?;
-        let root = find_child_tag_err(doc.root(), ROOT)?;
+        contents.parse()
+    }
 
-        let header = find_child_tag_err(root, HEADER)?;
-        let (file_version, database_version, creation_date) = parse_header(header)?;
+    /// Parses like `from_file`, but values of enumerated attributes that this
+    /// crate doesn't know about are kept or skipped and reported as warnings
+    /// instead of failing the whole parse.
+    pub fn from_file_lenient(filepath: &str) -> Result<(Self, Vec<ParseWarning>), ParseError> {
+        let contents = util::read_file(filepath)?;
+        Kanjidic::from_str_lenient(&contents)
+    }
 
-        let entries: Vec<_> = root
-            .children()
+    pub fn from_file_with_options(
+        filepath: &str,
+        options: ParseOptions,
+    ) -> Result<(Self, Vec<ParseWarning>), ParseError> {
+        let contents = util::read_file(filepath)?;
+        Kanjidic::from_str_with_options(&contents, options)
+    }
+
+    /// Parses only the entries whose literal the predicate accepts, which saves
+    /// the time and memory of parsing entries that would be discarded.
+    pub fn from_file_filtered<F>(filepath: &str, predicate: F) -> Result<Self, ParseError>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let contents = util::read_file(filepath)?;
+        Kanjidic::from_str_filtered(&contents, predicate)
+    }
+
+    /// Parses each file and merges them in order with `merge`, so entries in
+    /// later files replace those with the same literal in earlier ones.
+    pub fn from_files(filepaths: &[&str]) -> Result<Self, ParseError> {
+        let (first, rest) = filepaths
+            .split_first()
+            .ok_or_else(|| ParseError::ParseString("no kanjidic files given".to_owned()))?;
+        let mut dict = Kanjidic::from_file(first)?;
+        for filepath in rest {
+            dict.merge(Kanjidic::from_file(filepath)?);
+        }
+        Ok(dict)
+    }
+}
+
+#[cfg(feature = "sample-data")]
+const SAMPLE: &str = include_str!("../data/kanjidic2_sample.xml");
+
+impl Kanjidic {
+    /// Parses the bundled sample of twenty first-grade kanji (the numerals, the
+    /// days of the week, and a few more), for examples and trying the crate out
+    /// without the full file.
+    #[cfg(feature = "sample-data")]
+    pub fn sample() -> Kanjidic {
+        SAMPLE.parse().expect("bundled sample data is valid")
+    }
+
+    pub fn from_str_filtered<F>(contents: &str, predicate: F) -> Result<Self, ParseError>
+    where
+        F: Fn(&str) -> bool,
+    {
+        parse_kanjidic(
+            contents,
+            &mut Context::new(ParseOptions::default()),
+            &predicate,
+        )
+    }
+
+    /// Parses the entries along with the byte range of each character element
+    /// in the contents, from its opening to its closing tag, e.g. for editing
+    /// the source file in place. The header is skipped.
+    pub fn entries_with_positions(
+        contents: &str,
+    ) -> Result<Vec<(Entry, Range<usize>)>, ParseError> {
+        let doc = Document::parse(contents)?;
+        let root = find_child_tag_err(doc.root(), ROOT)?;
+        let mut ctx = Context::new(ParseOptions::default());
+        root.children()
             .filter(|c| c.is_element() && c.tag_name().name() == CHARACTER)
-            .map(|c| parse_entry(c))
-            .collect::<Result<Vec<_>, _>>()?;
-
-        Ok(Kanjidic {
-            file_version,
-            database_version,
-            creation_date,
-            entries,
-        })
+            .map(|c| Ok((parse_entry(c, &mut ctx)?, c.range())))
+            .collect()
+    }
+
+    /// Parses a document that has already been parsed with roxmltree, e.g. for
+    /// traversing it in other ways too. The Kanjidic owns its data, so it can
+    /// outlive the document.
+    pub fn from_document(doc: &Document) -> Result<Self, ParseError> {
+        parse_document(doc, &mut Context::new(ParseOptions::default()), &|_| true)
     }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        std::str::from_utf8(bytes)?.parse()
+    }
+
+    #[cfg(all(feature = "gzip", feature = "fs"))]
+    pub fn from_gzip_file(filepath: &str) -> Result<Self, ParseError> {
+        let file = std::fs::File::open(filepath).map_err(|err| ParseError::io(filepath, err))?;
+        Kanjidic::from_gzip_reader(file)
+    }
+
+    #[cfg(feature = "gzip")]
+    pub fn from_gzip_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        Kanjidic::from_gzip_reader(bytes)
+    }
+
+    #[cfg(feature = "gzip")]
+    fn from_gzip_reader<R: std::io::Read>(reader: R) -> Result<Self, ParseError> {
+        use std::io::Read;
+
+        let mut contents = String::new();
+        flate2::read::GzDecoder::new(reader).read_to_string(&mut contents)?;
+        contents.parse()
+    }
+
+    /// Downloads and parses the file at the URL, such as the kanjidic2.xml.gz
+    /// distributed by the EDRDG. The response is read a chunk at a time, and a
+    /// gzip-compressed response is decompressed as it arrives rather than
+    /// being held compressed first. The decompressed document is still held
+    /// in memory, since it is parsed as a whole.
+    #[cfg(feature = "download")]
+    pub async fn from_url(url: &str) -> Result<Self, ParseError> {
+        use std::io::Write;
+
+        const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+        let mut response = reqwest::get(url).await?.error_for_status()?;
+        // Enough of the start of the body to check for the gzip magic number.
+        let mut head = Vec::new();
+        while head.len() < GZIP_MAGIC.len() {
+            match response.chunk().await? {
+                Some(chunk) => head.extend_from_slice(&chunk),
+                None => break,
+            }
+        }
+
+        if head.starts_with(&GZIP_MAGIC) {
+            let mut decoder = flate2::write::GzDecoder::new(Vec::new());
+            decoder.write_all(&head)?;
+            while let Some(chunk) = response.chunk().await? {
+                decoder.write_all(&chunk)?;
+            }
+            Kanjidic::from_bytes(&decoder.finish()?)
+        } else {
+            while let Some(chunk) = response.chunk().await? {
+                head.extend_from_slice(&chunk);
+            }
+            Kanjidic::from_bytes(&head)
+        }
+    }
+
+    pub fn from_str_lenient(contents: &str) -> Result<(Self, Vec<ParseWarning>), ParseError> {
+        let options = ParseOptions {
+            lenient: true,
+            ..ParseOptions::default()
+        };
+        Kanjidic::from_str_with_options(contents, options)
+    }
+
+    pub fn from_str_with_options(
+        contents: &str,
+        options: ParseOptions,
+    ) -> Result<(Self, Vec<ParseWarning>), ParseError> {
+        let mut ctx = Context::new(options);
+        let dict = parse_kanjidic(contents, &mut ctx, &|_| true)?;
+        Ok((dict, ctx.warnings))
+    }
+}
+
+impl FromStr for Kanjidic {
+    type Err = ParseError;
+
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        Kanjidic::from_document(&Document::parse(contents)?)
+    }
+}
+
+struct Context {
+    options: ParseOptions,
+    warnings: Vec<ParseWarning>,
+    languages: HashSet<Arc<str>>,
+}
+
+impl Context {
+    fn new(options: ParseOptions) -> Self {
+        Context {
+            options,
+            warnings: Vec::new(),
+            languages: HashSet::new(),
+        }
+    }
+
+    // In lenient mode the error is recorded as a warning and the caller is
+    // expected to fall back; otherwise it is returned as is.
+    fn recover(&mut self, err: ParseEnumError) -> Result<(), ParseError> {
+        if self.options.lenient {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("recovered from parse error: {}", err);
+            self.warnings.push(ParseWarning::UnknownEnum(err));
+            Ok(())
+        } else {
+            Err(err.into())
+        }
+    }
+
+    fn intern_language(&mut self, lang: &str) -> Arc<str> {
+        match self.languages.get(lang) {
+            Some(interned) => interned.clone(),
+            None => {
+                let interned: Arc<str> = lang.into();
+                self.languages.insert(interned.clone());
+                interned
+            }
+        }
+    }
+
+    fn unknown_tag(&mut self, n: Node) {
+        if !n.is_element() {
+            return;
+        }
+        let tag = n.tag_name().name();
+        #[cfg(feature = "tracing")]
+        tracing::debug!("skipped unknown tag: {}", tag);
+        if !self.options.report_unknown_tags {
+            return;
+        }
+        let seen = self
+            .warnings
+            .iter()
+            .any(|w| matches!(*w, ParseWarning::UnknownTag(ref t) if t == tag));
+        if !seen {
+            self.warnings.push(ParseWarning::UnknownTag(tag.to_owned()));
+        }
+    }
+}
+
+// Entries whose literal is rejected by `keep` are skipped before the rest of
+// the entry is parsed.
+fn parse_kanjidic(
+    contents: &str,
+    ctx: &mut Context,
+    keep: &dyn Fn(&str) -> bool,
+) -> Result<Kanjidic, ParseError> {
+    let doc = Document::parse(contents)?;
+    parse_document(&doc, ctx, keep)
+}
+
+fn parse_document(
+    doc: &Document,
+    ctx: &mut Context,
+    keep: &dyn Fn(&str) -> bool,
+) -> Result<Kanjidic, ParseError> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("parse_kanjidic").entered();
+    let root = find_child_tag_err(doc.root(), ROOT)?;
+
+    let header = find_child_tag_err(root, HEADER)?;
+    let header = parse_header(header)?;
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        "parsed header: database version {}",
+        header.database_version
+    );
+
+    let mut entries = Vec::new();
+    for c in root
+        .children()
+        .filter(|c| c.is_element() && c.tag_name().name() == CHARACTER)
+    {
+        let literal = find_child_tag(c, LITERAL).and_then(|l| l.text());
+        if !literal.map_or(true, keep) {
+            continue;
+        }
+        if ctx.options.validate_dtd && !has_required_elements(c, literal, ctx) {
+            continue;
+        }
+        entries.push(parse_entry(c, ctx)?);
+    }
+    #[cfg(feature = "tracing")]
+    tracing::debug!("parsed {} entries", entries.len());
+
+    Ok(Kanjidic { header, entries })
+}
+
+fn has_required_elements(n: Node, literal: Option<&str>, ctx: &mut Context) -> bool {
+    let mut valid = true;
+    for &tag in &[LITERAL, CODEPOINT_GROUP, RADICAL_GROUP, MISC] {
+        if find_child_tag(n, tag).is_none() {
+            ctx.warnings.push(ParseWarning::MissingElement {
+                literal: literal.map(str::to_owned),
+                tag: tag.to_owned(),
+            });
+            valid = false;
+        }
+    }
+    valid
 }
 
 const_strs!(
@@ -186,7 +1960,7 @@ const_strs!(
     CREATION_DATE: "date_of_creation",
 );
 
-fn parse_header(header: Node) -> Result<(u32, String, String), ParseError> {
+fn parse_header(header: Node) -> Result<Header, ParseError> {
     let file_version_node = find_child_tag_err(header, FILE_VERSION)?;
     let file_version = get_node_text(file_version_node)?.parse()?;
 
@@ -196,7 +1970,11 @@ fn parse_header(header: Node) -> Result<(u32, String, String), ParseError> {
     let creation_date_node = find_child_tag_err(header, CREATION_DATE)?;
     let creation_date = get_node_text(creation_date_node)?.into_owned();
 
-    Ok((file_version, database_version, creation_date))
+    Ok(Header {
+        file_version,
+        database_version,
+        creation_date,
+    })
 }
 
 const_strs!(
@@ -214,10 +1992,24 @@ const_strs!(
 
     DIC_REF_GROUP: "dic_number",
 
+    QUERY_CODE_GROUP: "query_code",
+
     READING_GROUP: "reading_meaning",
 );
 
-fn parse_entry(n: Node) -> Result<Entry, ParseError> {
+fn check_literal(literal: String) -> Result<String, ParseError> {
+    let mut chars = literal.chars();
+    if chars.next().is_some() && chars.next().is_none() {
+        Ok(literal)
+    } else {
+        Err(ParseError::ParseString(format!(
+            "{}: literal is not a single character",
+            literal
+        )))
+    }
+}
+
+fn parse_entry(n: Node, ctx: &mut Context) -> Result<Entry, ParseError> {
     let mut literal_op: Option<String> = None;
     let mut codepoints_op: Option<Vec<Codepoint>> = None;
     let mut radicals_op: Option<Vec<Radical>> = None;
@@ -229,53 +2021,55 @@ fn parse_entry(n: Node) -> Result<Entry, ParseError> {
     for c in n.children() {
         let tag_name = c.tag_name().name();
         match tag_name {
-            LITERAL => literal_op = Some(get_node_text(c)?.into()),
-            CODEPOINT_GROUP => {
-                codepoints_op = Some(
-                    c.children()
-                        .filter(|cc| cc.tag_name().name() == CODEPOINT)
-                        .map(|cc| parse_codepoint(cc))
-                        .collect::<Result<Vec<_>, _>>()?,
-                )
-            }
-            RADICAL_GROUP => {
-                radicals_op = Some(
-                    c.children()
-                        .filter(|cc| cc.tag_name().name() == RADICAL)
-                        .map(|cc| parse_radical(cc))
-                        .collect::<Result<Vec<_>, _>>()?,
-                )
-            }
+            LITERAL => literal_op = Some(check_literal(get_node_text(c)?.into())?),
+            CODEPOINT_GROUP => codepoints_op = Some(parse_codepoint_group(c, ctx)?),
+            RADICAL_GROUP => radicals_op = Some(parse_radical_group(c, ctx)?),
             MISC => {
-                misc_op = Some(parse_misc(c)?);
+                misc_op = Some(parse_misc(c, ctx)?);
             }
-            DIC_REF_GROUP => dic_refs_op = Some(parse_dic_ref_group(c)?),
+            DIC_REF_GROUP => dic_refs_op = Some(parse_dic_ref_group(c, ctx)?),
             READING_GROUP => {
-                let (readings, nanori_readings) = parse_reading_meanings(c)?;
+                let (readings, nanori_readings) = parse_reading_meanings(c, ctx)?;
                 readings_meanings_op = Some(readings);
                 nanori_op = Some(nanori_readings);
             }
-            _ => {}
+            // The query codes are not parsed.
+            QUERY_CODE_GROUP => {}
+            _ => ctx.unknown_tag(c),
         }
     }
 
-    let misc = misc_op.ok_or(XmlError::MissingTag(MISC.to_owned()))?;
+    let misc = misc_op.ok_or_else(|| missing_tag(n, MISC))?;
 
     Ok(Entry {
-        literal: literal_op.ok_or(XmlError::MissingTag(LITERAL.to_owned()))?,
-        codepoints: codepoints_op.ok_or(XmlError::MissingTag(CODEPOINT_GROUP.to_owned()))?,
-        radicals: radicals_op.ok_or(XmlError::MissingTag(RADICAL_GROUP.to_owned()))?,
+        literal: literal_op.ok_or_else(|| missing_tag(n, LITERAL))?,
+        codepoints: codepoints_op.ok_or_else(|| missing_tag(n, CODEPOINT_GROUP))?,
+        radicals: radicals_op.ok_or_else(|| missing_tag(n, RADICAL_GROUP))?,
         grade: misc.grade,
         stroke_count: misc.stroke_count,
         stroke_miscounts: misc.stroke_miscounts,
+        variants: misc.variants,
+        rad_names: misc.rad_names,
         freq: misc.freq,
         old_jlpt: misc.old_jlpt,
-        dic_refs: dic_refs_op.unwrap_or(Vec::new()),
-        reading_meanings: readings_meanings_op.unwrap_or(Vec::new()),
-        nanori_readings: nanori_op.unwrap_or(Vec::new()),
+        dic_refs: dic_refs_op.unwrap_or_default(),
+        reading_meanings: readings_meanings_op.unwrap_or_default(),
+        nanori_readings: nanori_op.unwrap_or_default(),
+        components: Vec::new(),
     })
 }
 
+fn parse_codepoint_group(n: Node, ctx: &mut Context) -> Result<Vec<Codepoint>, ParseError> {
+    let mut codepoints = Vec::new();
+    for c in n.children() {
+        match c.tag_name().name() {
+            CODEPOINT => codepoints.push(parse_codepoint(c)?),
+            _ => ctx.unknown_tag(c),
+        }
+    }
+    Ok(codepoints)
+}
+
 fn parse_codepoint(n: Node) -> Result<Codepoint, ParseError> {
     let standard = get_node_attr(n, CODEPOINT_TYPE)?.into_owned();
     let value = get_node_text(n)?.into_owned();
@@ -283,28 +2077,44 @@ fn parse_codepoint(n: Node) -> Result<Codepoint, ParseError> {
     Ok(Codepoint { standard, value })
 }
 
-fn parse_radical(n: Node) -> Result<Radical, ParseError> {
+fn parse_radical_group(n: Node, ctx: &mut Context) -> Result<Vec<Radical>, ParseError> {
+    let mut radicals = Vec::new();
+    for c in n.children() {
+        match c.tag_name().name() {
+            RADICAL => radicals.extend(parse_radical(c, ctx)?),
+            _ => ctx.unknown_tag(c),
+        }
+    }
+    Ok(radicals)
+}
+
+fn parse_radical(n: Node, ctx: &mut Context) -> Result<Option<Radical>, ParseError> {
     let classification_attr = get_node_attr(n, RADICAL_TYPE)?;
     let classification = match classification_attr.as_ref() {
         "classical" => RadicalType::Classical,
         "nelson_c" => RadicalType::NelsonC,
         _ => {
             let valids = vec!["classical", "nelson_c"];
-            return Err(ParseEnumError::new(classification_attr.as_ref(), valids).into());
+            ctx.recover(ParseEnumError::new(classification_attr.as_ref(), valids))?;
+            return Ok(None);
         }
     };
-    let value_num = get_node_text(n)?.parse()?;
-    let value = radicals::index_radical(value_num)?;
+    let number = get_node_text(n)?.parse()?;
+    // An unknown number is kept rather than failing the whole file.
+    let value = radicals::index_radical(number).unwrap_or_default();
 
-    Ok(Radical {
+    Ok(Some(Radical {
         classification,
+        number,
         value,
-    })
+    }))
 }
 
 struct Misc {
     stroke_count: u32,
     stroke_miscounts: Vec<u32>,
+    variants: Vec<Variant>,
+    rad_names: Vec<String>,
 
     grade: Option<Grade>,
     freq: Option<u32>,
@@ -316,55 +2126,73 @@ const_strs!(
     STROKE_COUNT: "stroke_count",
     FREQ: "freq",
     JLPT: "jlpt",
+    VARIANT: "variant",
+    VARIANT_TYPE: "var_type",
+    RAD_NAME: "rad_name",
 );
 
-fn parse_misc(n: Node) -> Result<Misc, ParseError> {
+fn parse_misc(n: Node, ctx: &mut Context) -> Result<Misc, ParseError> {
     let mut grade: Option<Grade> = None;
     let mut stroke_counts: Vec<u32> = Vec::new();
     let mut freq: Option<u32> = None;
     let mut old_jlpt: Option<u32> = None;
+    let mut variants = Vec::new();
+    let mut rad_names = Vec::new();
 
     for c in n.children() {
         let tag_name = c.tag_name().name();
         let text = get_node_text(c);
         match tag_name {
             GRADE => {
-                grade = {
-                    let i = text?.parse()?;
-                    match i {
-                        1..=6 => Some(Grade::Kyouiku(i)),
-                        8 => Some(Grade::Jouyou),
-                        9 => Some(Grade::Jinmeiyou),
-                        10 => Some(Grade::JouyouVariant),
-                        _ => {
-                            let valids: Vec<_> = vec!["1", "2", "3", "4", "5", "6", "8", "9", "10"];
-                            return Err(ParseEnumError::new(&i.to_string(), valids).into());
-                        }
+                grade = match parse_grade(text?.parse()?) {
+                    Ok(g) => Some(g),
+                    Err(err) => {
+                        ctx.recover(err)?;
+                        None
                     }
                 }
             }
             STROKE_COUNT => stroke_counts.push(text?.parse()?),
             FREQ => freq = Some(text?.parse()?),
             JLPT => old_jlpt = Some(text?.parse()?),
-            _ => {}
+            VARIANT => variants.push(Variant {
+                typ: get_node_attr(c, VARIANT_TYPE)?.into_owned(),
+                value: text?.into_owned(),
+            }),
+            RAD_NAME => rad_names.push(text?.into_owned()),
+            _ => ctx.unknown_tag(c),
         }
     }
 
-    let stroke_count = stroke_counts
-        .first()
-        .ok_or(XmlError::MissingTag(STROKE_COUNT.to_owned()))?
-        .to_owned();
-    let stroke_miscounts: Vec<_> = stroke_counts[1..].to_vec();
+    let (&stroke_count, stroke_miscounts) = stroke_counts
+        .split_first()
+        .ok_or_else(|| missing_tag(n, STROKE_COUNT))?;
+    let stroke_miscounts = stroke_miscounts.to_vec();
 
     Ok(Misc {
         grade,
         stroke_count,
         stroke_miscounts,
+        variants,
+        rad_names,
         freq,
         old_jlpt,
     })
 }
 
+fn parse_grade(i: u32) -> Result<Grade, ParseEnumError> {
+    match i {
+        1..=6 => Ok(Grade::Kyouiku(i)),
+        8 => Ok(Grade::Jouyou),
+        9 => Ok(Grade::Jinmeiyou),
+        10 => Ok(Grade::JouyouVariant),
+        _ => {
+            let valids: Vec<_> = vec!["1", "2", "3", "4", "5", "6", "8", "9", "10"];
+            Err(ParseEnumError::new(&i.to_string(), valids))
+        }
+    }
+}
+
 const_strs!(
     DIC_REF: "dic_ref",
     DIC_REF_TYPE: "dr_type",
@@ -373,43 +2201,53 @@ const_strs!(
     MORO_PAGE: "m_page"
 );
 
-fn parse_dic_ref_group(n: Node) -> Result<Vec<DicRef>, ParseError> {
-    n.children()
-        .filter(|c| c.tag_name().name() == DIC_REF)
-        .map(|c| parse_dic_ref(c))
-        .collect()
+fn parse_dic_ref_group(n: Node, ctx: &mut Context) -> Result<Vec<DicRef>, ParseError> {
+    let mut dic_refs = Vec::new();
+    for c in n.children() {
+        match c.tag_name().name() {
+            DIC_REF => dic_refs.push(parse_dic_ref(c, ctx)?),
+            _ => ctx.unknown_tag(c),
+        }
+    }
+    Ok(dic_refs)
 }
 
-fn parse_dic_ref(n: Node) -> Result<DicRef, ParseError> {
+fn parse_dic_ref(n: Node, ctx: &mut Context) -> Result<DicRef, ParseError> {
     let num = get_node_text(n)?.into_owned();
     let typ_attr = get_node_attr(n, DIC_REF_TYPE)?;
-    let typ = typ_attr.as_ref();
-    let dic_ref = match typ {
-        "nelson_c" => DicRef::NelsonC(num),
-        "nelson_n" => DicRef::NelsonN(num),
-        "halpern_njecd" => DicRef::HalpernNJECD(num),
-        "halpern_kkd" => DicRef::HalpernKKD(num),
-        "halpern_kkld" => DicRef::HalpernKKLD(num),
-        "halpern_kkld_2ed" => DicRef::HalpernKKLD2(num),
-        "heisig" => DicRef::Heisig(num),
-        "heisig6" => DicRef::Heisig6(num),
-        "gakken" => DicRef::Gakken(num),
-        "oneill_names" => DicRef::OneillNames(num),
-        "oneill_kk" => DicRef::OneillKK(num),
-        "henshall" => DicRef::Henshall(num),
-        "henshall3" => DicRef::Henshall3(num),
-        "sh_kk" => DicRef::SHKK(num),
-        "sh_kk2" => DicRef::SHKK2(num),
-        "sakade" => DicRef::Sakade(num),
-        "jf_cards" => DicRef::JFCards(num),
-        "tutt_cards" => DicRef::TuttCards(num),
-        "crowley" => DicRef::Crowley(num),
-        "kanji_in_context" => DicRef::InContext(num),
-        "busy_people" => DicRef::BusyPeople(num),
-        "kodansha_compact" => DicRef::KodanshaCompact(num),
-        "maniette" => DicRef::Maniette(num),
-        "moro" => {
-            let vol = match n.attribute(MORO_VOL) {
+    let kind = match typ_attr.parse() {
+        Ok(kind) => kind,
+        Err(err) => {
+            ctx.recover(err)?;
+            return Ok(DicRef::Other(typ_attr.into_owned(), num));
+        }
+    };
+    let dic_ref = match kind {
+        DicRefKind::NelsonC => DicRef::NelsonC(num),
+        DicRefKind::NelsonN => DicRef::NelsonN(num),
+        DicRefKind::HalpernNJECD => DicRef::HalpernNJECD(num),
+        DicRefKind::HalpernKKD => DicRef::HalpernKKD(num),
+        DicRefKind::HalpernKKLD => DicRef::HalpernKKLD(num),
+        DicRefKind::HalpernKKLD2 => DicRef::HalpernKKLD2(num),
+        DicRefKind::Heisig => DicRef::Heisig(num),
+        DicRefKind::Heisig6 => DicRef::Heisig6(num),
+        DicRefKind::Gakken => DicRef::Gakken(num),
+        DicRefKind::OneillNames => DicRef::OneillNames(num),
+        DicRefKind::OneillKK => DicRef::OneillKK(num),
+        DicRefKind::Henshall => DicRef::Henshall(num),
+        DicRefKind::Henshall3 => DicRef::Henshall3(num),
+        DicRefKind::SHKK => DicRef::SHKK(num),
+        DicRefKind::SHKK2 => DicRef::SHKK2(num),
+        DicRefKind::Sakade => DicRef::Sakade(num),
+        DicRefKind::JFCards => DicRef::JFCards(num),
+        DicRefKind::TuttCards => DicRef::TuttCards(num),
+        DicRefKind::Crowley => DicRef::Crowley(num),
+        DicRefKind::InContext => DicRef::InContext(num),
+        DicRefKind::BusyPeople => DicRef::BusyPeople(num),
+        DicRefKind::KodanshaCompact => DicRef::KodanshaCompact(num),
+        DicRefKind::Maniette => DicRef::Maniette(num),
+        DicRefKind::Moro => {
+            let volume = match n.attribute(MORO_VOL) {
                 Some(v) => Some(v.parse()?),
                 None => None,
             };
@@ -418,36 +2256,11 @@ fn parse_dic_ref(n: Node) -> Result<DicRef, ParseError> {
                 None => None,
             };
 
-            DicRef::Moro(num, vol, page)
-        }
-        _ => {
-            let valids = vec![
-                "nelson_c",
-                "nelson_c",
-                "halpern_njecd",
-                "halpern_kkd",
-                "halpern_kkld",
-                "halpern_kkld_2ed",
-                "heisig",
-                "heisig6",
-                "gakken",
-                "oneill_names",
-                "oneill_kk",
-                "henshall",
-                "henshall3",
-                "sh_kk",
-                "sh_kk2",
-                "sakade",
-                "jf_cards",
-                "tutt_cards",
-                "crowley",
-                "kanji_in_context",
-                "busy_people",
-                "kodansha_compact",
-                "maniette",
-                "moro",
-            ];
-            return Err(ParseEnumError::new(typ, valids).into());
+            DicRef::Moro(MoroRef {
+                index: num,
+                volume,
+                page,
+            })
         }
     };
 
@@ -466,7 +2279,10 @@ const_strs!(
     NANORI: "nanori"
 );
 
-fn parse_reading_meanings(n: Node) -> Result<(Vec<ReadingMeaning>, Vec<String>), ParseError> {
+fn parse_reading_meanings(
+    n: Node,
+    ctx: &mut Context,
+) -> Result<(Vec<ReadingMeaning>, Vec<String>), ParseError> {
     let mut reading_meanings = Vec::new();
     let mut nanori_readings = Vec::new();
 
@@ -474,21 +2290,22 @@ fn parse_reading_meanings(n: Node) -> Result<(Vec<ReadingMeaning>, Vec<String>),
         let tag_name = c.tag_name().name();
         match tag_name {
             READING_MEANING => {
-                let rmgroup = parse_reading_group(c)?;
+                let rmgroup = parse_reading_group(c, ctx)?;
                 reading_meanings.push(rmgroup);
             }
             NANORI => {
-                let text = get_node_text(c)?.into_owned();
-                nanori_readings.push(text);
+                if let Some(text) = get_node_text_opt(c) {
+                    nanori_readings.push(text.into_owned());
+                }
             }
-            _ => {}
+            _ => ctx.unknown_tag(c),
         }
     }
 
     Ok((reading_meanings, nanori_readings))
 }
 
-fn parse_reading_group(n: Node) -> Result<ReadingMeaning, ParseError> {
+fn parse_reading_group(n: Node, ctx: &mut Context) -> Result<ReadingMeaning, ParseError> {
     let mut readings = Vec::new();
     let mut meanings = Vec::new();
 
@@ -496,22 +2313,40 @@ fn parse_reading_group(n: Node) -> Result<ReadingMeaning, ParseError> {
         let tag_name = c.tag_name().name();
         match tag_name {
             READING => {
-                let reading = parse_reading(c)?;
+                let reading = parse_reading(c, ctx)?;
                 readings.push(reading);
             }
+            // A blank meaning carries nothing, so it's skipped rather than kept
+            // as an empty string.
             MEANING => {
-                let language = c.attribute(MEANING_LANG).unwrap_or("en").to_owned();
-                let content = get_node_text(c)?.into_owned();
+                let content = match get_node_text_opt(c) {
+                    Some(text) => text.into_owned(),
+                    None => continue,
+                };
+                let language = match c.attribute(MEANING_LANG) {
+                    Some(lang) if ctx.options.normalize_languages => {
+                        ctx.intern_language(&normalize_language(lang))
+                    }
+                    Some(lang) => ctx.intern_language(lang),
+                    None => {
+                        let lang = ctx.options.default_meaning_lang.clone();
+                        ctx.intern_language(&lang)
+                    }
+                };
                 meanings.push(Meaning { content, language });
             }
-            _ => {}
+            _ => ctx.unknown_tag(c),
         }
     }
 
     Ok(ReadingMeaning { readings, meanings })
 }
 
-fn parse_reading(n: Node) -> Result<Reading, ParseError> {
+fn normalize_language(lang: &str) -> String {
+    lang.trim().to_lowercase().replace('_', "-")
+}
+
+fn parse_reading(n: Node, ctx: &mut Context) -> Result<Reading, ParseError> {
     let value = get_node_text(n)?.into_owned();
     let typ_attr = get_node_attr(n, READING_TYPE)?;
     let typ = match typ_attr.as_ref() {
@@ -520,40 +2355,42 @@ fn parse_reading(n: Node) -> Result<Reading, ParseError> {
         "korean_h" => ReadingType::KoreanH,
         "vietnam" => ReadingType::Vietnam,
         "ja_on" => {
-            let jouyou_approved = get_jouyou_approved(n);
-            let onyomi_typ = match get_node_attr(n, READING_ONYOMI_TYPE) {
-                Ok(ty) => match ty.as_ref() {
+            let status = get_reading_status(n);
+            let onyomi_typ = match get_node_attr_opt(n, READING_ONYOMI_TYPE) {
+                Some(ty) => match ty.as_ref() {
                     "kan" => OnyomiType::Kan,
                     "go" => OnyomiType::Go,
                     "tou" => OnyomiType::Tou,
                     "kan'you" => OnyomiType::Kanyou,
                     _ => {
                         let valids = vec!["kan", "go", "tou", "kan'you"];
-                        return Err(ParseEnumError::new(ty.as_ref(), valids).into());
+                        ctx.recover(ParseEnumError::new(ty.as_ref(), valids))?;
+                        OnyomiType::Other(ty.into_owned())
                     }
                 },
-                Err(_) => OnyomiType::None,
+                None => OnyomiType::None,
             };
-            ReadingType::Onyomi(jouyou_approved, onyomi_typ)
+            ReadingType::Onyomi(status, onyomi_typ)
         }
         "ja_kun" => {
-            let jouyou_approved = get_jouyou_approved(n);
-            ReadingType::Kunyomi(jouyou_approved)
+            let status = get_reading_status(n);
+            ReadingType::Kunyomi(status)
         }
         _ => {
             let valids = vec![
                 "pinyin", "korean_r", "korean_h", "vietnam", "ja_on", "ja_kun",
             ];
-            return Err(ParseEnumError::new(typ_attr.as_ref(), valids).into());
+            ctx.recover(ParseEnumError::new(typ_attr.as_ref(), valids))?;
+            ReadingType::Other(typ_attr.into_owned())
         }
     };
 
     Ok(Reading { value, typ })
 }
 
-fn get_jouyou_approved(n: Node) -> bool {
-    match get_node_attr(n, READING_JA_STATUS) {
-        Ok(_) => true,
-        Err(_) => false,
-    }
+fn get_reading_status(n: Node) -> Option<ReadingStatus> {
+    n.attribute(READING_JA_STATUS).map(|status| match status {
+        "jy" => ReadingStatus::Jouyou,
+        _ => ReadingStatus::Other(status.to_owned()),
+    })
 }
@@ -0,0 +1,58 @@
+use super::{Entry, Kanjidic};
+
+/// A column of the TSV produced by `Kanjidic::to_anki_tsv`. Lists are joined
+/// with ", " except meanings, which are joined with "; " since they may contain
+/// commas themselves. Missing values are left empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnkiField {
+    Literal,
+    Onyomi,
+    Kunyomi,
+    Nanori,
+    /// The English meanings.
+    Meanings,
+    StrokeCount,
+    /// The grade number, see `Grade::number`.
+    Grade,
+    /// The old four-level JLPT, see `Entry::jlpt_level`.
+    Jlpt,
+    Frequency,
+}
+
+impl Kanjidic {
+    /// Writes a row per entry with the fields as columns, in the format Anki
+    /// imports as tab-separated text. Fields containing tabs, newlines, or
+    /// double quotes are quoted, with double quotes inside doubled.
+    pub fn to_anki_tsv(&self, fields: &[AnkiField]) -> String {
+        let mut tsv = String::new();
+        for e in &self.entries {
+            let row: Vec<_> = fields.iter().map(|&f| escape(&field(e, f))).collect();
+            tsv.push_str(&row.join("\t"));
+            tsv.push('\n');
+        }
+        tsv
+    }
+}
+
+fn field(e: &Entry, f: AnkiField) -> String {
+    let number = |n: Option<u32>| n.map(|n| n.to_string()).unwrap_or_default();
+    match f {
+        AnkiField::Literal => e.literal.clone(),
+        AnkiField::Onyomi => e.onyomi().join(", "),
+        AnkiField::Kunyomi => e.kunyomi().join(", "),
+        AnkiField::Nanori => e.nanori_readings.join(", "),
+        AnkiField::Meanings => e.english_meanings().collect::<Vec<_>>().join("; "),
+        AnkiField::StrokeCount => e.stroke_count.to_string(),
+        AnkiField::Grade => number(e.grade.map(|g| g.number())),
+        AnkiField::Jlpt => number(e.old_jlpt),
+        AnkiField::Frequency => number(e.freq),
+    }
+}
+
+fn escape(value: &str) -> String {
+    if value.contains(&['\t', '\n', '\r', '"'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
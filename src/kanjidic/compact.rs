@@ -0,0 +1,127 @@
+use super::{reading_matches, strip_reading_markers, Entry, Grade, Kanjidic, ReadingType};
+use crate::util;
+use std::sync::Arc;
+
+/// A read-only, smaller form of the dictionary for memory-constrained targets.
+///
+/// Only the fields needed for lookups are kept: codepoints, radicals, dictionary
+/// references, and the non-Japanese readings are dropped. The literal is stored
+/// as a char rather than a String, the readings and meanings as boxed strs
+/// without spare capacity, and each list as a boxed slice instead of a Vec.
+/// Meaning languages share the interned allocations of the Kanjidic. The
+/// rmgroup structure is flattened.
+#[derive(Debug, Clone)]
+pub struct CompactKanjidic {
+    entries: Box<[CompactEntry]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompactEntry {
+    pub literal: char,
+    pub stroke_count: u32,
+    pub grade: Option<Grade>,
+    pub freq: Option<u32>,
+    pub old_jlpt: Option<u32>,
+    pub onyomi: Box<[Box<str>]>,
+    pub kunyomi: Box<[Box<str>]>,
+    pub nanori: Box<[Box<str>]>,
+    pub meanings: Box<[CompactMeaning]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompactMeaning {
+    pub content: Box<str>,
+    pub language: Arc<str>,
+}
+
+impl From<&Kanjidic> for CompactKanjidic {
+    fn from(dict: &Kanjidic) -> Self {
+        let entries = dict.entries.iter().filter_map(compact_entry).collect();
+        CompactKanjidic { entries }
+    }
+}
+
+impl CompactKanjidic {
+    pub fn entries(&self) -> &[CompactEntry] {
+        &self.entries
+    }
+
+    pub fn find_literal(&self, literal: &str) -> Option<&CompactEntry> {
+        let mut chars = literal.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => self.find_char(c),
+            _ => None,
+        }
+    }
+
+    pub fn find_char(&self, c: char) -> Option<&CompactEntry> {
+        self.entries.iter().find(|e| e.literal == c)
+    }
+
+    pub fn filter<F>(&self, predicate: F) -> Vec<&CompactEntry>
+    where
+        F: Fn(&CompactEntry) -> bool,
+    {
+        self.entries.iter().filter(|e| predicate(e)).collect()
+    }
+
+    pub fn filter_meaning<F>(&self, predicate: F) -> Vec<&CompactEntry>
+    where
+        F: Fn(&CompactMeaning) -> bool,
+    {
+        self.filter(|e| e.meanings.iter().any(&predicate))
+    }
+
+    pub fn by_jlpt(&self, level: u32) -> Vec<&CompactEntry> {
+        self.filter(|e| e.old_jlpt == Some(level))
+    }
+
+    /// See `Kanjidic::search_reading`.
+    pub fn search_reading(&self, reading: &str) -> Vec<&CompactEntry> {
+        let query = strip_reading_markers(&util::to_hiragana(reading));
+        self.filter(|e| {
+            e.onyomi
+                .iter()
+                .chain(e.kunyomi.iter())
+                .chain(e.nanori.iter())
+                .any(|r| reading_matches(r, &query))
+        })
+    }
+}
+
+fn compact_entry(e: &Entry) -> Option<CompactEntry> {
+    let literal = e.as_char()?;
+    let onyomi = e
+        .readings()
+        .filter(|r| matches!(r.typ, ReadingType::Onyomi(..)))
+        .map(|r| r.value.as_str().into())
+        .collect();
+    let kunyomi = e
+        .readings()
+        .filter(|r| matches!(r.typ, ReadingType::Kunyomi(..)))
+        .map(|r| r.value.as_str().into())
+        .collect();
+    let meanings = e
+        .meanings()
+        .map(|m| CompactMeaning {
+            content: m.content.as_str().into(),
+            language: m.language.clone(),
+        })
+        .collect();
+
+    Some(CompactEntry {
+        literal,
+        stroke_count: e.stroke_count,
+        grade: e.grade,
+        freq: e.freq,
+        old_jlpt: e.old_jlpt,
+        onyomi,
+        kunyomi,
+        nanori: e
+            .nanori_readings
+            .iter()
+            .map(|n| n.as_str().into())
+            .collect(),
+        meanings,
+    })
+}
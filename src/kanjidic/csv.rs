@@ -0,0 +1,78 @@
+use super::{Entry, Kanjidic};
+use ::csv::Writer;
+
+/// A column of the CSV produced by `Kanjidic::to_csv`, named in the header row
+/// by `Column::name`. Lists are joined with "; ". Missing values are left empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Literal,
+    StrokeCount,
+    /// The grade number, see `Grade::number`.
+    Grade,
+    Frequency,
+    /// The old four-level JLPT, see `Entry::jlpt_level`.
+    Jlpt,
+    /// The first English meaning, see `Entry::primary_meaning`.
+    FirstMeaning,
+    /// All English meanings.
+    Meanings,
+    Onyomi,
+    Kunyomi,
+    Nanori,
+}
+
+impl Column {
+    /// The name of the column in the header row.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Column::Literal => "literal",
+            Column::StrokeCount => "strokes",
+            Column::Grade => "grade",
+            Column::Frequency => "freq",
+            Column::Jlpt => "jlpt",
+            Column::FirstMeaning => "meaning",
+            Column::Meanings => "meanings",
+            Column::Onyomi => "on",
+            Column::Kunyomi => "kun",
+            Column::Nanori => "nanori",
+        }
+    }
+}
+
+impl Kanjidic {
+    /// Writes a header row with the names of the columns, then a row per
+    /// entry. Fields are quoted as needed.
+    pub fn to_csv(&self, columns: &[Column]) -> String {
+        // Writing to a Vec can't fail, every row has the same number of fields
+        // as the header, and the fields are all UTF-8, so none of these errors
+        // can happen short of a bug.
+        let mut writer = Writer::from_writer(Vec::new());
+        writer
+            .write_record(columns.iter().map(Column::name))
+            .expect("writing to a Vec cannot fail");
+        for e in &self.entries {
+            let row = columns.iter().map(|&c| field(e, c));
+            writer
+                .write_record(row)
+                .expect("writing to a Vec cannot fail");
+        }
+        let bytes = writer.into_inner().expect("writing to a Vec cannot fail");
+        String::from_utf8(bytes).expect("the fields are all UTF-8")
+    }
+}
+
+fn field(e: &Entry, c: Column) -> String {
+    let number = |n: Option<u32>| n.map(|n| n.to_string()).unwrap_or_default();
+    match c {
+        Column::Literal => e.literal.clone(),
+        Column::StrokeCount => e.stroke_count.to_string(),
+        Column::Grade => number(e.grade.map(|g| g.number())),
+        Column::Frequency => number(e.freq),
+        Column::Jlpt => number(e.old_jlpt),
+        Column::FirstMeaning => e.primary_meaning().unwrap_or_default().to_owned(),
+        Column::Meanings => e.english_meanings().collect::<Vec<_>>().join("; "),
+        Column::Onyomi => e.onyomi().join("; "),
+        Column::Kunyomi => e.kunyomi().join("; "),
+        Column::Nanori => e.nanori_readings.join("; "),
+    }
+}
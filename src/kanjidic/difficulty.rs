@@ -0,0 +1,60 @@
+use super::{Entry, Grade};
+
+// The stroke count and frequency rank at or above which those parts of the
+// score are at their maximum. The frequency ranks in the file stop at 2501.
+const MAX_STROKES: f32 = 30.0;
+const MAX_FREQ: f32 = 2500.0;
+
+/// The relative weights of the parts of `Entry::difficulty_score`. The weights
+/// don't need to add up to anything; the score is their weighted average.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifficultyWeights {
+    pub strokes: f32,
+    pub grade: f32,
+    pub frequency: f32,
+}
+
+impl Default for DifficultyWeights {
+    fn default() -> Self {
+        DifficultyWeights {
+            strokes: 1.0,
+            grade: 1.0,
+            frequency: 1.0,
+        }
+    }
+}
+
+impl Entry {
+    /// A difficulty from 0 to 100, higher for more complex and rarer kanji,
+    /// with the default weights. See `difficulty_score_with`.
+    pub fn difficulty_score(&self) -> f32 {
+        self.difficulty_score_with(&DifficultyWeights::default())
+    }
+
+    /// The weighted average of three parts, each from 0 to 1, scaled to 100:
+    ///
+    /// - strokes: the stroke count over 30, capped at 1;
+    /// - grade: 0 for Kyouiku grade 1 up to 5/7 for grade 6, 6/7 for the
+    ///   secondary school Jouyou kanji, and 1 for the Jinmeiyou kanji and
+    ///   kanji without a grade;
+    /// - frequency: the frequency rank over 2500, capped at 1, and 1 for kanji
+    ///   without a rank.
+    ///
+    /// If all weights are zero the score is 0.
+    pub fn difficulty_score_with(&self, weights: &DifficultyWeights) -> f32 {
+        let strokes = (self.stroke_count as f32 / MAX_STROKES).min(1.0);
+        let grade = match self.grade {
+            Some(Grade::Kyouiku(i)) => (i.saturating_sub(1) as f32 / 7.0).min(1.0),
+            Some(Grade::Jouyou) => 6.0 / 7.0,
+            Some(Grade::Jinmeiyou) | Some(Grade::JouyouVariant) | None => 1.0,
+        };
+        let frequency = self.freq.map_or(1.0, |f| (f as f32 / MAX_FREQ).min(1.0));
+
+        let total = weights.strokes + weights.grade + weights.frequency;
+        if total <= 0.0 {
+            return 0.0;
+        }
+        let sum = weights.strokes * strokes + weights.grade * grade + weights.frequency * frequency;
+        100.0 * sum / total
+    }
+}
@@ -0,0 +1,40 @@
+use super::{Entry, Kanjidic};
+
+impl Kanjidic {
+    /// Finds entries with a meaning word within the Levenshtein distance of the
+    /// term, ignoring case. Meanings are split into words on anything but
+    /// letters and digits, and each entry is ranked by its closest word. The
+    /// results are sorted by distance, then by their order in the dictionary.
+    pub fn search_meaning_fuzzy(&self, term: &str, max_distance: usize) -> Vec<(&Entry, usize)> {
+        let term: Vec<char> = term.to_lowercase().chars().collect();
+        let mut matches: Vec<_> = self
+            .entries
+            .iter()
+            .filter_map(|e| {
+                e.meanings()
+                    .flat_map(|m| m.content.split(|c: char| !c.is_alphanumeric()))
+                    .filter(|w| !w.is_empty())
+                    .map(|w| levenshtein(&term, &w.to_lowercase().chars().collect::<Vec<_>>()))
+                    .min()
+                    .filter(|&d| d <= max_distance)
+                    .map(|d| (e, d))
+            })
+            .collect();
+        matches.sort_by_key(|&(_, d)| d);
+        matches
+    }
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        cur[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + if ca == cb { 0 } else { 1 };
+            cur[j + 1] = substitution.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
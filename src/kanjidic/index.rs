@@ -0,0 +1,91 @@
+use super::{strip_reading_markers, Entry, Kanjidic, ReadingType};
+use crate::util;
+use std::collections::{HashMap, HashSet};
+
+/// The Japanese readings of every entry, normalized once as in
+/// `Kanjidic::search_reading`, for repeated searches. Each reading is keyed both
+/// in full and by its stem before the okurigana. The index borrows the entries
+/// from the Kanjidic it was built from, which can't be changed while the index
+/// is alive.
+#[derive(Debug, Clone)]
+pub struct ReadingIndex<'a> {
+    readings: HashMap<String, Vec<&'a Entry>>,
+}
+
+/// The entries keyed by literal, for repeated lookups. `Kanjidic::find_literal`
+/// scans the entries, and the batch lookups of Kanjidic build one of these on
+/// each call, so building it once is faster when looking up many times.
+#[derive(Debug, Clone)]
+pub struct LiteralIndex<'a> {
+    literals: HashMap<&'a str, &'a Entry>,
+}
+
+impl Kanjidic {
+    pub fn build_literal_index(&self) -> LiteralIndex<'_> {
+        let literals = self
+            .entries
+            .iter()
+            .map(|e| (e.literal.as_str(), e))
+            .collect();
+        LiteralIndex { literals }
+    }
+
+    pub fn build_reading_index(&self) -> ReadingIndex<'_> {
+        let mut readings: HashMap<String, Vec<&Entry>> = HashMap::new();
+        for e in &self.entries {
+            let japanese = e
+                .readings()
+                .filter(|r| matches!(r.typ, ReadingType::Onyomi(..) | ReadingType::Kunyomi(..)))
+                .map(|r| r.value.as_str())
+                .chain(e.nanori_readings.iter().map(|n| n.as_str()));
+            for r in japanese {
+                let reading = util::to_hiragana(r);
+                let stem = reading.split('.').next().unwrap_or_default();
+                for key in &[strip_reading_markers(&reading), strip_reading_markers(stem)] {
+                    let matches = readings.entry(key.clone()).or_default();
+                    // Entries are visited in order, so any earlier match of
+                    // this entry is the last one in the list.
+                    if !matches.last().is_some_and(|m| std::ptr::eq(*m, e)) {
+                        matches.push(e);
+                    }
+                }
+            }
+        }
+        ReadingIndex { readings }
+    }
+}
+
+impl<'a> ReadingIndex<'a> {
+    /// Returns the same entries as `Kanjidic::search_reading`, in the same
+    /// order.
+    pub fn search(&self, reading: &str) -> Vec<&'a Entry> {
+        let query = strip_reading_markers(&util::to_hiragana(reading));
+        self.readings.get(&query).cloned().unwrap_or_default()
+    }
+}
+
+impl<'a> LiteralIndex<'a> {
+    pub fn find_literal(&self, literal: &str) -> Option<&'a Entry> {
+        self.literals.get(literal).copied()
+    }
+
+    pub fn find_char(&self, c: char) -> Option<&'a Entry> {
+        let mut buf = [0; 4];
+        self.find_literal(c.encode_utf8(&mut buf))
+    }
+
+    /// See `Kanjidic::find_literals`.
+    pub fn find_literals(&self, literals: &[&str]) -> Vec<Option<&'a Entry>> {
+        literals.iter().map(|l| self.find_literal(l)).collect()
+    }
+
+    /// See `Kanjidic::subset`.
+    pub fn subset<I: IntoIterator<Item = char>>(&self, chars: I) -> Vec<&'a Entry> {
+        let mut seen = HashSet::new();
+        chars
+            .into_iter()
+            .filter_map(|c| self.find_char(c))
+            .filter(|e| seen.insert(e.literal.as_str()))
+            .collect()
+    }
+}
@@ -0,0 +1,126 @@
+use super::{Entry, Grade, Kanjidic, RadicalType, ReadingType};
+use serde_json::{json, Map, Value};
+use std::io::{self, Write};
+
+impl Kanjidic {
+    /// Returns the entries as a JSON array of the objects returned by
+    /// `Entry::to_value`, which have the following shape. Readings are
+    /// flattened out of their rmgroups and keyed by type; fields without a
+    /// value are emitted as `null`.
+    ///
+    /// ```json
+    /// {
+    ///     "literal": "亜",
+    ///     "codepoints": { "ucs": "4e9c", "jis208": "1-16-01" },
+    ///     "radicals": { "classical": 7, "nelson_c": 1 },
+    ///     "stroke_count": 7,
+    ///     "stroke_miscounts": [],
+    ///     "grade": 8,
+    ///     "freq": 1509,
+    ///     "jlpt": 1,
+    ///     "on": ["ア"],
+    ///     "kun": ["つ.ぐ"],
+    ///     "nanori": ["や", "つぎ"],
+    ///     "pinyin": ["ya4"],
+    ///     "korean_r": ["a"],
+    ///     "korean_h": ["아"],
+    ///     "vietnam": [],
+    ///     "meanings": [{ "lang": "en", "value": "Asia" }, ...]
+    /// }
+    /// ```
+    pub fn to_json(&self) -> String {
+        self.json_value().to_string()
+    }
+
+    /// Like `to_json`, but indented over multiple lines for reading.
+    pub fn to_json_pretty(&self) -> String {
+        format!("{:#}", self.json_value())
+    }
+
+    /// Writes each entry as a compact JSON object on its own line, as JSON
+    /// Lines. The entries are written one at a time rather than built into a
+    /// single string first.
+    pub fn write_jsonl<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for e in &self.entries {
+            serde_json::to_writer(&mut writer, &e.to_value())?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()
+    }
+
+    fn json_value(&self) -> Value {
+        Value::Array(self.entries.iter().map(Entry::to_value).collect())
+    }
+}
+
+impl Entry {
+    /// Returns the entry as a flat JSON object with the readings and meanings
+    /// collected into arrays; see `Kanjidic::to_json` for the schema.
+    pub fn to_value(&self) -> Value {
+        entry_value(self)
+    }
+}
+
+fn entry_value(e: &Entry) -> Value {
+    let codepoints: Map<_, _> = e
+        .codepoints
+        .iter()
+        .map(|c| (c.standard.clone(), Value::from(c.value.as_str())))
+        .collect();
+
+    let radicals: Map<_, _> = e
+        .radicals
+        .iter()
+        .map(|r| {
+            let key = match r.classification {
+                RadicalType::Classical => "classical",
+                RadicalType::NelsonC => "nelson_c",
+            };
+            (key.to_owned(), Value::from(r.number))
+        })
+        .collect();
+
+    let readings = e.readings();
+    let mut on = Vec::new();
+    let mut kun = Vec::new();
+    let mut pinyin = Vec::new();
+    let mut korean_r = Vec::new();
+    let mut korean_h = Vec::new();
+    let mut vietnam = Vec::new();
+    for r in readings {
+        let list = match r.typ {
+            ReadingType::Onyomi(..) => &mut on,
+            ReadingType::Kunyomi(..) => &mut kun,
+            ReadingType::Pinyin => &mut pinyin,
+            ReadingType::KoreanR => &mut korean_r,
+            ReadingType::KoreanH => &mut korean_h,
+            ReadingType::Vietnam => &mut vietnam,
+            ReadingType::Other(_) => continue,
+        };
+        list.push(r.value.as_str());
+    }
+
+    let meanings: Vec<_> = e
+        .meanings()
+        .map(|m| json!({ "lang": &*m.language, "value": m.content }))
+        .collect();
+
+    json!({
+        "literal": e.literal,
+        "codepoints": codepoints,
+        "radicals": radicals,
+        "stroke_count": e.stroke_count,
+        "stroke_miscounts": e.stroke_miscounts,
+        "grade": e.grade.as_ref().map(Grade::number),
+        "freq": e.freq,
+        "jlpt": e.old_jlpt,
+        "on": on,
+        "kun": kun,
+        "nanori": e.nanori_readings,
+        "pinyin": pinyin,
+        "korean_r": korean_r,
+        "korean_h": korean_h,
+        "vietnam": vietnam,
+        "meanings": meanings,
+    })
+}
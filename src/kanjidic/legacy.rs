@@ -0,0 +1,331 @@
+use super::{
+    check_literal, parse_grade, Codepoint, DicRef, Entry, Header, Kanjidic, Meaning, MoroRef,
+    OnyomiType, Radical, RadicalType, Reading, ReadingMeaning, ReadingType,
+};
+use crate::errors::ParseError;
+use crate::radicals;
+use crate::util;
+#[cfg(feature = "fs")]
+use encoding_rs::EUC_JP;
+#[cfg(feature = "fs")]
+use std::fs;
+use std::sync::Arc;
+
+// The legacy kanjidic/kanjd212 files have one kanji per line: the literal, its
+// JIS code in hex, then space-separated fields identified by a letter code.
+// Readings are bare kana (katakana for on, hiragana for kun), and meanings are
+// enclosed in braces. A T1 field switches the following readings to nanori and
+// T2 to radical names.
+//
+// The legacy files carry no structured header, so the header fields of the
+// resulting Kanjidic are left empty.
+impl Kanjidic {
+    #[cfg(feature = "fs")]
+    pub fn from_legacy_file(filepath: &str) -> Result<Self, ParseError> {
+        Kanjidic::from_legacy_str(&read_euc_jp(filepath)?)
+    }
+
+    /// Like `from_legacy_file`, but for kanjd212, whose codes are JIS X 0212
+    /// and are kept as jis212 codepoints.
+    #[cfg(feature = "fs")]
+    pub fn from_legacy_file_212(filepath: &str) -> Result<Self, ParseError> {
+        Kanjidic::from_legacy_str_212(&read_euc_jp(filepath)?)
+    }
+
+    pub fn from_legacy_str(contents: &str) -> Result<Self, ParseError> {
+        parse_legacy(contents, "jis208")
+    }
+
+    pub fn from_legacy_str_212(contents: &str) -> Result<Self, ParseError> {
+        parse_legacy(contents, "jis212")
+    }
+}
+
+#[cfg(feature = "fs")]
+fn read_euc_jp(filepath: &str) -> Result<String, ParseError> {
+    let bytes = fs::read(filepath).map_err(|err| ParseError::io(filepath, err))?;
+    let (contents, _, had_errors) = EUC_JP.decode(&bytes);
+    if had_errors {
+        return Err(ParseError::ParseString(
+            "file is not valid EUC-JP".to_owned(),
+        ));
+    }
+    Ok(contents.into_owned())
+}
+
+// The standard is that of the JIS code of each line: jis208 for kanjidic and
+// jis212 for kanjd212.
+fn parse_legacy(contents: &str, standard: &str) -> Result<Kanjidic, ParseError> {
+    // All meanings are English, and share the language allocation.
+    let english: Arc<str> = "en".into();
+    let entries = contents
+        .lines()
+        .filter(|l| !l.trim().is_empty() && !l.starts_with('#') && !l.starts_with('＃'))
+        .map(|l| parse_line(l, standard, &english))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Kanjidic {
+        header: Header {
+            file_version: 0,
+            database_version: String::new(),
+            creation_date: String::new(),
+        },
+        entries,
+    })
+}
+
+enum ReadingMode {
+    Normal,
+    Nanori,
+    RadicalName,
+}
+
+fn parse_line(line: &str, standard: &str, english: &Arc<str>) -> Result<Entry, ParseError> {
+    let mut tokens = tokenize(line).into_iter();
+    let literal = tokens
+        .next()
+        .ok_or_else(|| ParseError::ParseString("kanji literal not found".to_owned()))?
+        .to_owned();
+    let literal = check_literal(literal)?;
+    let jis = tokens
+        .next()
+        .ok_or_else(|| ParseError::ParseString(format!("{}: JIS code not found", literal)))?;
+
+    let mut codepoints = vec![Codepoint {
+        standard: standard.to_owned(),
+        value: jis_hex_to_kuten(jis)?,
+    }];
+    let mut bushu: Option<u32> = None;
+    let mut classical: Option<u32> = None;
+    let mut stroke_counts = Vec::new();
+    let mut grade = None;
+    let mut freq = None;
+    let mut old_jlpt = None;
+    let mut dic_refs = Vec::new();
+    let mut moro: Option<MoroRef> = None;
+    let mut readings = Vec::new();
+    let mut meanings = Vec::new();
+    let mut nanori_readings = Vec::new();
+    let mut rad_names = Vec::new();
+    let mut mode = ReadingMode::Normal;
+
+    for token in tokens {
+        if token.starts_with('{') {
+            let content = token.trim_start_matches('{').trim_end_matches('}');
+            meanings.push(Meaning {
+                content: content.to_owned(),
+                language: english.clone(),
+            });
+            continue;
+        }
+
+        let first = token.trim_start_matches('-').chars().next().unwrap_or(' ');
+        if util::is_katakana(first) || util::is_hiragana(first) {
+            match mode {
+                ReadingMode::Normal => {
+                    let typ = if util::is_katakana(first) {
+                        ReadingType::Onyomi(None, OnyomiType::None)
+                    } else {
+                        ReadingType::Kunyomi(None)
+                    };
+                    readings.push(Reading {
+                        value: token.to_owned(),
+                        typ,
+                    });
+                }
+                ReadingMode::Nanori => nanori_readings.push(token.to_owned()),
+                ReadingMode::RadicalName => rad_names.push(token.to_owned()),
+            }
+            continue;
+        }
+
+        let (code, value) = match split_field(token) {
+            Some(field) => field,
+            None => continue,
+        };
+        match code {
+            "U" => codepoints.push(Codepoint {
+                standard: "ucs".to_owned(),
+                value: value.to_owned(),
+            }),
+            "B" => bushu = Some(value.parse()?),
+            "C" => classical = Some(value.parse()?),
+            "G" => grade = Some(parse_grade(value.parse()?)?),
+            "S" => stroke_counts.push(value.parse()?),
+            "F" => freq = Some(value.parse()?),
+            "J" => old_jlpt = Some(value.parse()?),
+            "Y" => readings.push(Reading {
+                value: value.to_owned(),
+                typ: ReadingType::Pinyin,
+            }),
+            "W" => readings.push(Reading {
+                value: value.to_owned(),
+                typ: ReadingType::KoreanR,
+            }),
+            "T" => {
+                mode = match value {
+                    "1" => ReadingMode::Nanori,
+                    "2" => ReadingMode::RadicalName,
+                    _ => ReadingMode::Normal,
+                }
+            }
+            "MN" => {
+                moro.get_or_insert_with(empty_moro).index = value.to_owned();
+            }
+            "MP" => {
+                let m = moro.get_or_insert_with(empty_moro);
+                let mut parts = value.splitn(2, '.');
+                m.volume = parts.next().and_then(|v| v.parse().ok());
+                m.page = parts.next().and_then(|p| p.parse().ok());
+            }
+            _ => {
+                if let Some(dic_ref) = legacy_dic_ref(code, value) {
+                    dic_refs.push(dic_ref);
+                }
+            }
+        }
+    }
+
+    if let Some(m) = moro {
+        dic_refs.push(DicRef::Moro(m));
+    }
+
+    // The B field is the radical as classified by Nelson; C is only present
+    // when the classical radical differs from it.
+    let bushu =
+        bushu.ok_or_else(|| ParseError::ParseString(format!("{}: radical not found", literal)))?;
+    let mut radicals = Vec::new();
+    match classical {
+        Some(c) => {
+            radicals.push(legacy_radical(RadicalType::Classical, c));
+            radicals.push(legacy_radical(RadicalType::NelsonC, bushu));
+        }
+        None => radicals.push(legacy_radical(RadicalType::Classical, bushu)),
+    }
+
+    let (&stroke_count, stroke_miscounts) = stroke_counts
+        .split_first()
+        .ok_or_else(|| ParseError::ParseString(format!("{}: stroke count not found", literal)))?;
+    let stroke_miscounts = stroke_miscounts.to_vec();
+
+    let reading_meanings = if readings.is_empty() && meanings.is_empty() {
+        Vec::new()
+    } else {
+        vec![ReadingMeaning { readings, meanings }]
+    };
+
+    Ok(Entry {
+        literal,
+        codepoints,
+        reading_meanings,
+        nanori_readings,
+        radicals,
+        stroke_count,
+        stroke_miscounts,
+        // The X cross-reference fields aren't kept, like the other query codes.
+        variants: Vec::new(),
+        rad_names,
+        grade,
+        freq,
+        old_jlpt,
+        dic_refs,
+        components: Vec::new(),
+    })
+}
+
+// Splits the line on spaces, keeping brace-enclosed meanings together.
+fn tokenize(line: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut rest = line.trim();
+    while !rest.is_empty() {
+        let end = if rest.starts_with('{') {
+            rest.find('}').map_or(rest.len(), |i| i + 1)
+        } else {
+            rest.find(' ').unwrap_or(rest.len())
+        };
+        tokens.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    tokens
+}
+
+// Fields start with an ASCII letter code; tokens that don't, like stray
+// full-width symbols, aren't fields and are skipped.
+fn split_field(token: &str) -> Option<(&str, &str)> {
+    let code_len = match token.chars().next() {
+        Some('D') | Some('M') => 2,
+        Some('I') if token.starts_with("IN") => 2,
+        Some('X') | Some('Z') => return Some((token, "")),
+        Some(c) if c.is_ascii() => 1,
+        _ => return None,
+    };
+    // The value may follow the code directly with a non-ASCII character, so
+    // the code length is counted in chars.
+    let end = token
+        .char_indices()
+        .nth(code_len)
+        .map_or(token.len(), |(i, _)| i);
+    Some((&token[..end], &token[end..]))
+}
+
+// Both bytes of a JIS code are in 0x21..=0x7E, and are offset by 0x20 from the
+// ku and ten of its kuten.
+fn jis_hex_to_kuten(jis: &str) -> Result<String, ParseError> {
+    let code = u32::from_str_radix(jis, 16)?;
+    let (high, low) = (code >> 8, code & 0xff);
+    if code > 0xffff || !(0x21..=0x7e).contains(&high) || !(0x21..=0x7e).contains(&low) {
+        return Err(ParseError::ParseString(format!(
+            "invalid JIS code: {}",
+            jis
+        )));
+    }
+    Ok(format!("1-{}-{:02}", high - 0x20, low - 0x20))
+}
+
+fn legacy_radical(classification: RadicalType, number: u32) -> Radical {
+    Radical {
+        classification,
+        number,
+        value: radicals::index_radical(number).unwrap_or_default(),
+    }
+}
+
+fn empty_moro() -> MoroRef {
+    MoroRef {
+        index: String::new(),
+        volume: None,
+        page: None,
+    }
+}
+
+fn legacy_dic_ref(code: &str, value: &str) -> Option<DicRef> {
+    let num = value.to_owned();
+    let dic_ref = match code {
+        "N" => DicRef::NelsonC(num),
+        "V" => DicRef::NelsonN(num),
+        "H" => DicRef::HalpernNJECD(num),
+        "DP" => DicRef::HalpernKKD(num),
+        "DK" => DicRef::HalpernKKLD(num),
+        "DL" => DicRef::HalpernKKLD2(num),
+        "L" => DicRef::Heisig(num),
+        "DN" => DicRef::Heisig6(num),
+        "K" => DicRef::Gakken(num),
+        "O" => DicRef::OneillNames(num),
+        "DO" => DicRef::OneillKK(num),
+        "E" => DicRef::Henshall(num),
+        "IN" => DicRef::SHKK(num),
+        "DA" => DicRef::SHKK2(num),
+        "DS" => DicRef::Sakade(num),
+        "DF" => DicRef::JFCards(num),
+        "DH" => DicRef::Henshall3(num),
+        "DT" => DicRef::TuttCards(num),
+        "DC" => DicRef::Crowley(num),
+        "DJ" => DicRef::InContext(num),
+        "DB" => DicRef::BusyPeople(num),
+        "DG" => DicRef::KodanshaCompact(num),
+        "DM" => DicRef::Maniette(num),
+        // Query codes, cross-references, and misclassifications aren't kept.
+        _ => return None,
+    };
+    Some(dic_ref)
+}
@@ -0,0 +1,86 @@
+use super::{
+    reading_matches, strip_reading_markers, Entry, Grade, Kanjidic, RadicalType, ReadingType,
+};
+use crate::util;
+
+type Predicate<'a> = Box<dyn Fn(&Entry) -> bool + 'a>;
+
+/// A set of constraints on entries, all checked in a single pass by `collect`.
+/// Built with `Kanjidic::query`.
+pub struct Query<'a> {
+    dict: &'a Kanjidic,
+    predicates: Vec<Predicate<'a>>,
+}
+
+impl Kanjidic {
+    pub fn query(&self) -> Query<'_> {
+        Query {
+            dict: self,
+            predicates: Vec::new(),
+        }
+    }
+}
+
+impl<'a> Query<'a> {
+    pub fn grade(self, grade: Grade) -> Self {
+        self.filter(move |e| e.grade == Some(grade))
+    }
+
+    pub fn min_strokes(self, strokes: u32) -> Self {
+        self.filter(move |e| e.stroke_count >= strokes)
+    }
+
+    pub fn max_strokes(self, strokes: u32) -> Self {
+        self.filter(move |e| e.stroke_count <= strokes)
+    }
+
+    /// Matches the old four-level JLPT, see `Entry::jlpt_level`.
+    pub fn jlpt(self, level: u32) -> Self {
+        self.filter(move |e| e.jlpt_level() == Some(level))
+    }
+
+    /// Matches the Kangxi radical number in the classical classification.
+    pub fn radical(self, radical_number: u32) -> Self {
+        self.filter(move |e| {
+            e.radicals
+                .iter()
+                .any(|r| r.classification == RadicalType::Classical && r.number == radical_number)
+        })
+    }
+
+    /// Matches an on or kun reading, compared as in `Kanjidic::search_reading`.
+    pub fn reading(self, reading: &str) -> Self {
+        let query = strip_reading_markers(&util::to_hiragana(reading));
+        self.filter(move |e| {
+            e.readings()
+                .filter(|r| matches!(r.typ, ReadingType::Onyomi(..) | ReadingType::Kunyomi(..)))
+                .any(|r| reading_matches(&r.value, &query))
+        })
+    }
+
+    /// Matches a meaning in any language equal to the text, ignoring ASCII case.
+    pub fn has_meaning(self, meaning: &str) -> Self {
+        let meaning = meaning.to_owned();
+        self.filter(move |e| {
+            e.meanings()
+                .any(|m| m.content.eq_ignore_ascii_case(&meaning))
+        })
+    }
+
+    pub fn filter<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Entry) -> bool + 'a,
+    {
+        self.predicates.push(Box::new(predicate));
+        self
+    }
+
+    pub fn collect(self) -> Vec<&'a Entry> {
+        let predicates = self.predicates;
+        self.dict
+            .entries
+            .iter()
+            .filter(|e| predicates.iter().all(|p| p(e)))
+            .collect()
+    }
+}
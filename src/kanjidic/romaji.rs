@@ -0,0 +1,214 @@
+use super::{Reading, ReadingType};
+use crate::util;
+
+/// The romanization systems of `Reading::to_romaji_with`. They differ mostly on
+/// the s, t, z, d, and h rows: し is shi in Hepburn but si in the other two,
+/// つ is tsu or tu, and ぢ and づ are ji and zu in Hepburn, zi and zu in Kunrei,
+/// and di and du in Nihon-shiki, which also keeps を as wo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RomajiSystem {
+    Hepburn,
+    Kunrei,
+    NihonShiki,
+}
+
+impl Reading {
+    /// Romanizes the reading in Hepburn. See `to_romaji_with`.
+    pub fn to_romaji(&self) -> Option<String> {
+        self.to_romaji_with(RomajiSystem::Hepburn)
+    }
+
+    /// Romanizes an on or kun reading, dropping the okurigana separator and
+    /// affix markers, e.g. "た.べる" becomes "taberu". A syllabic ん is written
+    /// n' before a vowel or y, and a long vowel mark repeats the vowel before
+    /// it. Readings of other types, or with kana this can't romanize, give
+    /// `None`.
+    pub fn to_romaji_with(&self, system: RomajiSystem) -> Option<String> {
+        match self.typ {
+            ReadingType::Onyomi(..) | ReadingType::Kunyomi(..) => {}
+            _ => return None,
+        }
+        let kana: Vec<char> = util::to_hiragana(&self.value)
+            .chars()
+            .filter(|&c| c != '.' && c != '-')
+            .collect();
+        romanize(&kana, system)
+    }
+}
+
+fn romanize(kana: &[char], system: RomajiSystem) -> Option<String> {
+    let mut romaji = String::new();
+    let mut geminate = false;
+    let mut i = 0;
+    while i < kana.len() {
+        let c = kana[i];
+        i += 1;
+        match c {
+            'っ' => {
+                geminate = true;
+                continue;
+            }
+            'ん' => {
+                romaji.push('n');
+                if let Some(&next) = kana.get(i) {
+                    let syllable = syllable(next, system).unwrap_or_default();
+                    if syllable.starts_with(|c| is_vowel(c) || c == 'y') {
+                        romaji.push('\'');
+                    }
+                }
+                continue;
+            }
+            'ー' => {
+                let vowel = romaji.chars().last().filter(|&v| is_vowel(v))?;
+                romaji.push(vowel);
+                continue;
+            }
+            _ => {}
+        }
+
+        let mut s = syllable(c, system)?;
+        if let Some(small) = kana.get(i).and_then(|&n| small_y(n)) {
+            if let Some(stem) = s.strip_suffix('i').filter(|stem| !stem.is_empty()) {
+                s = yoon(stem, small, system);
+                i += 1;
+            }
+        }
+        if geminate {
+            match s.chars().next() {
+                Some('c') if system == RomajiSystem::Hepburn => romaji.push('t'),
+                Some(first) if !is_vowel(first) => romaji.push(first),
+                _ => {}
+            }
+            geminate = false;
+        }
+        romaji.push_str(&s);
+    }
+    Some(romaji)
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'i' | 'u' | 'e' | 'o')
+}
+
+fn small_y(c: char) -> Option<char> {
+    match c {
+        'ゃ' => Some('a'),
+        'ゅ' => Some('u'),
+        'ょ' => Some('o'),
+        _ => None,
+    }
+}
+
+// The combination of an i-row kana with a small ya, yu, or yo. Hepburn writes
+// sh, ch, and j without the y.
+fn yoon(stem: &str, vowel: char, system: RomajiSystem) -> String {
+    match (system, stem) {
+        (RomajiSystem::Hepburn, "sh")
+        | (RomajiSystem::Hepburn, "ch")
+        | (RomajiSystem::Hepburn, "j") => {
+            format!("{}{}", stem, vowel)
+        }
+        _ => format!("{}y{}", stem, vowel),
+    }
+}
+
+fn syllable(c: char, system: RomajiSystem) -> Option<String> {
+    use self::RomajiSystem::*;
+
+    let s = match (c, system) {
+        ('し', Hepburn) => "shi",
+        ('し', _) => "si",
+        ('ち', Hepburn) => "chi",
+        ('ち', _) => "ti",
+        ('つ', Hepburn) => "tsu",
+        ('つ', _) => "tu",
+        ('ふ', Hepburn) => "fu",
+        ('ふ', _) => "hu",
+        ('じ', Hepburn) => "ji",
+        ('じ', _) => "zi",
+        ('ぢ', Hepburn) => "ji",
+        ('ぢ', Kunrei) => "zi",
+        ('ぢ', NihonShiki) => "di",
+        ('づ', NihonShiki) => "du",
+        ('づ', _) => "zu",
+        ('を', NihonShiki) => "wo",
+        ('を', _) => "o",
+        ('ゐ', NihonShiki) => "wi",
+        ('ゐ', _) => "i",
+        ('ゑ', NihonShiki) => "we",
+        ('ゑ', _) => "e",
+        _ => return common_syllable(c).map(str::to_owned),
+    };
+    Some(s.to_owned())
+}
+
+// The kana romanized the same way in all three systems.
+fn common_syllable(c: char) -> Option<&'static str> {
+    let s = match c {
+        'あ' | 'ぁ' => "a",
+        'い' | 'ぃ' => "i",
+        'う' | 'ぅ' => "u",
+        'え' | 'ぇ' => "e",
+        'お' | 'ぉ' => "o",
+        'か' => "ka",
+        'き' => "ki",
+        'く' => "ku",
+        'け' => "ke",
+        'こ' => "ko",
+        'が' => "ga",
+        'ぎ' => "gi",
+        'ぐ' => "gu",
+        'げ' => "ge",
+        'ご' => "go",
+        'さ' => "sa",
+        'す' => "su",
+        'せ' => "se",
+        'そ' => "so",
+        'ざ' => "za",
+        'ず' => "zu",
+        'ぜ' => "ze",
+        'ぞ' => "zo",
+        'た' => "ta",
+        'て' => "te",
+        'と' => "to",
+        'だ' => "da",
+        'で' => "de",
+        'ど' => "do",
+        'な' => "na",
+        'に' => "ni",
+        'ぬ' => "nu",
+        'ね' => "ne",
+        'の' => "no",
+        'は' => "ha",
+        'ひ' => "hi",
+        'へ' => "he",
+        'ほ' => "ho",
+        'ば' => "ba",
+        'び' => "bi",
+        'ぶ' => "bu",
+        'べ' => "be",
+        'ぼ' => "bo",
+        'ぱ' => "pa",
+        'ぴ' => "pi",
+        'ぷ' => "pu",
+        'ぺ' => "pe",
+        'ぽ' => "po",
+        'ま' => "ma",
+        'み' => "mi",
+        'む' => "mu",
+        'め' => "me",
+        'も' => "mo",
+        'や' | 'ゃ' => "ya",
+        'ゆ' | 'ゅ' => "yu",
+        'よ' | 'ょ' => "yo",
+        'ら' => "ra",
+        'り' => "ri",
+        'る' => "ru",
+        'れ' => "re",
+        'ろ' => "ro",
+        'わ' | 'ゎ' => "wa",
+        'ゔ' => "vu",
+        _ => return None,
+    };
+    Some(s)
+}
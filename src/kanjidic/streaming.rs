@@ -0,0 +1,60 @@
+use super::{parse_entry, Context, Entry, Kanjidic, ParseOptions, CHARACTER};
+use crate::errors::{ParseError, XmlError};
+use quick_xml::events::Event;
+use quick_xml::{Reader, Writer};
+use roxmltree::Document;
+use std::io::BufRead;
+
+// The file is read with quick-xml's event reader, and the events of each
+// character element are written back out into a buffer that is parsed on its
+// own with roxmltree. Only one entry is held in memory at a time, and the DOM
+// parsing of the entries is shared with `from_str`. Positions in errors are
+// relative to the start of the character element.
+impl Kanjidic {
+    /// Parses the entries one at a time, passing each to the callback as soon
+    /// as it is read. The header is skipped.
+    pub fn parse_streaming<R, F>(reader: R, mut callback: F) -> Result<(), ParseError>
+    where
+        R: BufRead,
+        F: FnMut(Entry),
+    {
+        let mut reader = Reader::from_reader(reader);
+        let mut ctx = Context::new(ParseOptions::default());
+        let mut buf = Vec::new();
+        let mut writer: Option<Writer<Vec<u8>>> = None;
+        let mut depth = 0;
+
+        loop {
+            let event = reader.read_event_into(&mut buf).map_err(XmlError::from)?;
+            match event {
+                Event::Eof => break,
+                Event::Start(ref start)
+                    if writer.is_none() && start.local_name().as_ref() == CHARACTER.as_bytes() =>
+                {
+                    writer = Some(Writer::new(Vec::new()));
+                    depth = 0;
+                }
+                _ => {}
+            }
+
+            if let Some(ref mut w) = writer {
+                match event {
+                    Event::Start(_) => depth += 1,
+                    Event::End(_) => depth -= 1,
+                    _ => {}
+                }
+                w.write_event(event)?;
+
+                if depth == 0 {
+                    let fragment = writer.take().map(Writer::into_inner).unwrap_or_default();
+                    let fragment = std::str::from_utf8(&fragment)?;
+                    let doc = Document::parse(fragment)?;
+                    callback(parse_entry(doc.root_element(), &mut ctx)?);
+                }
+            }
+            buf.clear();
+        }
+
+        Ok(())
+    }
+}
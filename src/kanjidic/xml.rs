@@ -0,0 +1,200 @@
+use super::{
+    DicRef, Entry, Kanjidic, OnyomiType, RadicalType, Reading, ReadingStatus, ReadingType,
+    CHARACTER, CODEPOINT, CODEPOINT_GROUP, CODEPOINT_TYPE, CREATION_DATE, DATABASE_VERSION,
+    DIC_REF, DIC_REF_GROUP, DIC_REF_TYPE, FILE_VERSION, FREQ, GRADE, HEADER, JLPT, LITERAL,
+    MEANING, MEANING_LANG, MISC, MORO_PAGE, MORO_VOL, NANORI, RADICAL, RADICAL_GROUP, RADICAL_TYPE,
+    RAD_NAME, READING, READING_GROUP, READING_JA_STATUS, READING_MEANING, READING_ONYOMI_TYPE,
+    READING_TYPE, ROOT, STROKE_COUNT, VARIANT, VARIANT_TYPE,
+};
+use std::fmt::Write;
+
+// Writes the dictionary back out in the kanjidic2 schema, with the elements of
+// each character in DTD order. Everything the parser keeps is written, so the
+// output parses back into an equal Kanjidic; the query codes and the DOCTYPE
+// with its DTD aren't kept and so aren't written. Meanings in English are
+// written without an m_lang, as in the original file.
+impl Kanjidic {
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        open(&mut xml, ROOT);
+        open(&mut xml, HEADER);
+        element(
+            &mut xml,
+            FILE_VERSION,
+            &self.header.file_version.to_string(),
+        );
+        element(&mut xml, DATABASE_VERSION, &self.header.database_version);
+        element(&mut xml, CREATION_DATE, &self.header.creation_date);
+        close(&mut xml, HEADER);
+        for e in &self.entries {
+            write_entry(&mut xml, e);
+        }
+        close(&mut xml, ROOT);
+        xml
+    }
+}
+
+fn write_entry(xml: &mut String, e: &Entry) {
+    open(xml, CHARACTER);
+    element(xml, LITERAL, &e.literal);
+
+    open(xml, CODEPOINT_GROUP);
+    for c in &e.codepoints {
+        element_attr(xml, CODEPOINT, &[(CODEPOINT_TYPE, &c.standard)], &c.value);
+    }
+    close(xml, CODEPOINT_GROUP);
+    open(xml, RADICAL_GROUP);
+    for r in &e.radicals {
+        let typ = match r.classification {
+            RadicalType::Classical => "classical",
+            RadicalType::NelsonC => "nelson_c",
+        };
+        element_attr(xml, RADICAL, &[(RADICAL_TYPE, typ)], &r.number.to_string());
+    }
+    close(xml, RADICAL_GROUP);
+    open(xml, MISC);
+    if let Some(grade) = e.grade {
+        element(xml, GRADE, &grade.number().to_string());
+    }
+    for count in e.all_stroke_counts() {
+        element(xml, STROKE_COUNT, &count.to_string());
+    }
+    for v in &e.variants {
+        element_attr(xml, VARIANT, &[(VARIANT_TYPE, &v.typ)], &v.value);
+    }
+    if let Some(freq) = e.freq {
+        element(xml, FREQ, &freq.to_string());
+    }
+    for name in &e.rad_names {
+        element(xml, RAD_NAME, name);
+    }
+    if let Some(jlpt) = e.old_jlpt {
+        element(xml, JLPT, &jlpt.to_string());
+    }
+    close(xml, MISC);
+
+    if !e.dic_refs.is_empty() {
+        open(xml, DIC_REF_GROUP);
+        for d in &e.dic_refs {
+            write_dic_ref(xml, d);
+        }
+        close(xml, DIC_REF_GROUP);
+    }
+
+    if !e.reading_meanings.is_empty() || !e.nanori_readings.is_empty() {
+        open(xml, READING_GROUP);
+        for rm in &e.reading_meanings {
+            open(xml, READING_MEANING);
+            for r in &rm.readings {
+                write_reading(xml, r);
+            }
+            for m in &rm.meanings {
+                if &*m.language == "en" {
+                    element(xml, MEANING, &m.content);
+                } else {
+                    element_attr(xml, MEANING, &[(MEANING_LANG, &m.language)], &m.content);
+                }
+            }
+            close(xml, READING_MEANING);
+        }
+        for n in &e.nanori_readings {
+            element(xml, NANORI, n);
+        }
+        close(xml, READING_GROUP);
+    }
+    close(xml, CHARACTER);
+}
+
+fn write_dic_ref(xml: &mut String, d: &DicRef) {
+    let typ = match (d, d.kind()) {
+        (DicRef::Other(typ, _), _) => typ.as_str(),
+        (_, Some(kind)) => kind.as_str(),
+        (_, None) => return,
+    };
+    match d.as_moro() {
+        Some(moro) => {
+            let volume = moro.volume.map(|v| v.to_string());
+            let page = moro.page.map(|p| format!("{:04}", p));
+            let mut attrs = vec![(DIC_REF_TYPE, typ)];
+            if let Some(ref v) = volume {
+                attrs.push((MORO_VOL, v));
+            }
+            if let Some(ref p) = page {
+                attrs.push((MORO_PAGE, p));
+            }
+            element_attr(xml, DIC_REF, &attrs, &moro.index);
+        }
+        None => element_attr(xml, DIC_REF, &[(DIC_REF_TYPE, typ)], d.value()),
+    }
+}
+
+fn write_reading(xml: &mut String, r: &Reading) {
+    let (typ, on_type, status) = match r.typ {
+        ReadingType::Pinyin => ("pinyin", None, None),
+        ReadingType::KoreanR => ("korean_r", None, None),
+        ReadingType::KoreanH => ("korean_h", None, None),
+        ReadingType::Vietnam => ("vietnam", None, None),
+        ReadingType::Onyomi(ref status, ref on_type) => {
+            let on_type = match *on_type {
+                OnyomiType::Kan => Some("kan"),
+                OnyomiType::Go => Some("go"),
+                OnyomiType::Tou => Some("tou"),
+                OnyomiType::Kanyou => Some("kan'you"),
+                OnyomiType::None => None,
+                OnyomiType::Other(ref typ) => Some(typ.as_str()),
+            };
+            ("ja_on", on_type, status.as_ref())
+        }
+        ReadingType::Kunyomi(ref status) => ("ja_kun", None, status.as_ref()),
+        ReadingType::Other(ref typ) => (typ.as_str(), None, None),
+    };
+
+    let mut attrs = vec![(READING_TYPE, typ)];
+    if let Some(on_type) = on_type {
+        attrs.push((READING_ONYOMI_TYPE, on_type));
+    }
+    match status {
+        Some(ReadingStatus::Jouyou) => attrs.push((READING_JA_STATUS, "jy")),
+        Some(ReadingStatus::Other(s)) => attrs.push((READING_JA_STATUS, s)),
+        None => {}
+    }
+    element_attr(xml, READING, &attrs, &r.value);
+}
+
+fn open(xml: &mut String, tag: &str) {
+    let _ = writeln!(xml, "<{}>", tag);
+}
+
+fn close(xml: &mut String, tag: &str) {
+    let _ = writeln!(xml, "</{}>", tag);
+}
+
+fn element(xml: &mut String, tag: &str, text: &str) {
+    element_attr(xml, tag, &[], text);
+}
+
+fn element_attr(xml: &mut String, tag: &str, attrs: &[(&str, &str)], text: &str) {
+    xml.push('<');
+    xml.push_str(tag);
+    for (name, value) in attrs {
+        // Writing to a String can't fail.
+        let _ = write!(xml, " {}=\"{}\"", name, escape(value));
+    }
+    let _ = writeln!(xml, ">{}</{}>", escape(text), tag);
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
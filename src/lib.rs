@@ -1,8 +1,11 @@
 #[macro_use]
-mod util;
+pub mod util;
 
+#[cfg(feature = "fs")]
+pub mod dictionaries;
 pub mod errors;
 pub mod jmdict;
+pub mod jmnedict;
 pub mod kanjidic;
 pub mod radicals;
 pub mod tatoeba;
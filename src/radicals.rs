@@ -1,4 +1,63 @@
-use crate::errors::ParseEnumError;
+use crate::errors::{ParseEnumError, ParseError};
+#[cfg(feature = "fs")]
+use crate::util;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// The component radicals of kanji, as listed in a KRADFILE. Each line gives a
+/// kanji and its components separated by spaces, e.g. "語 : 言 口 五", and lines
+/// starting with '#' are comments. The file is read as UTF-8, as in the
+/// kradfile-u distribution; the original kradfile and kradfile2 are EUC-JP and
+/// need to be converted first.
+#[derive(Debug, Clone, Default)]
+pub struct Radicals {
+    components: HashMap<char, Vec<char>>,
+}
+
+impl Radicals {
+    #[cfg(feature = "fs")]
+    pub fn from_kradfile(filepath: &str) -> Result<Self, ParseError> {
+        let contents = util::read_file(filepath)?;
+        contents.parse()
+    }
+
+    pub fn components(&self, kanji: char) -> Option<&[char]> {
+        self.components.get(&kanji).map(Vec::as_slice)
+    }
+}
+
+impl FromStr for Radicals {
+    type Err = ParseError;
+
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let mut components = HashMap::new();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || ParseError::ParseString(format!("invalid KRADFILE line: {}", line));
+            let (kanji, parts) = line.split_once(':').ok_or_else(invalid)?;
+            let mut kanji_chars = kanji.trim().chars();
+            let kanji = match (kanji_chars.next(), kanji_chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(invalid()),
+            };
+            let parts = parts
+                .split_whitespace()
+                .map(|p| {
+                    let mut chars = p.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => Ok(c),
+                        _ => Err(invalid()),
+                    }
+                })
+                .collect::<Result<_, _>>()?;
+            components.insert(kanji, parts);
+        }
+        Ok(Radicals { components })
+    }
+}
 
 pub fn index_radical(i: u32) -> Result<String, ParseEnumError> {
     let rad = match i {
@@ -1,6 +1,10 @@
 use crate::errors::ParseError;
+#[cfg(feature = "fs")]
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::BufRead;
+#[cfg(feature = "fs")]
+use std::io::BufReader;
+#[cfg(feature = "fs")]
 use std::path::Path;
 
 pub struct Tatoeba {
@@ -20,10 +24,12 @@ impl Tatoeba {
             .collect()
     }
 
+    #[cfg(feature = "fs")]
     pub fn from_file_jp<P: AsRef<Path>>(filepath: P) -> Result<Self, ParseError> {
         Tatoeba::from_file(filepath, Some(|s: &str| s == "jpn"))
     }
 
+    #[cfg(feature = "fs")]
     pub fn from_file<F, P: AsRef<Path>>(
         filepath: P,
         language_filter: Option<F>,
@@ -31,8 +37,17 @@ impl Tatoeba {
     where
         F: Fn(&str) -> bool,
     {
-        let file = File::open(filepath)?;
-        let reader = BufReader::new(file);
+        let file = File::open(&filepath).map_err(|err| ParseError::io(&filepath, err))?;
+        Tatoeba::from_reader(BufReader::new(file), language_filter)
+    }
+
+    pub fn from_reader<F, R: BufRead>(
+        reader: R,
+        language_filter: Option<F>,
+    ) -> Result<Self, ParseError>
+    where
+        F: Fn(&str) -> bool,
+    {
         let mut entries = Vec::new();
 
         let language_filter: Box<dyn Fn(&str) -> bool> = match language_filter {
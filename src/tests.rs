@@ -1,8 +1,46 @@
+use crate::errors::{ParseError, ParseWarning, ValidationError, XmlError};
 use crate::jmdict::JMDict;
-use crate::kanjidic::Kanjidic;
+use crate::jmnedict::JMnedict;
+use crate::kanjidic::{
+    DicRef, DicRefKind, DifficultyWeights, Entry, Grade, Header, Kanjidic, LiteralIndex, Meaning,
+    OnyomiType, ParseOptions, RadicalType, Reading, ReadingIndex, ReadingStatus, ReadingType,
+    RomajiSystem, SortKey,
+};
+use crate::radicals::Radicals;
 use crate::tatoeba::Tatoeba;
+use crate::util;
+use std::collections::HashSet;
+#[cfg(feature = "fs")]
 use std::env;
+#[cfg(feature = "fs")]
+use std::fs;
 
+const KANJIDIC_SAMPLE: &str = include_str!("../fixtures/kanjidic2_sample.xml");
+const JMNEDICT_SAMPLE: &str = include_str!("../fixtures/jmnedict_sample.xml");
+
+fn kanjidic_sample() -> Kanjidic {
+    KANJIDIC_SAMPLE.parse().unwrap()
+}
+
+fn kanjidic_with(characters: &str) -> Kanjidic {
+    kanjidic_xml(characters).parse().unwrap()
+}
+
+fn kanjidic_xml(characters: &str) -> String {
+    format!(
+        "<kanjidic2>\
+         <header>\
+         <file_version>4</file_version>\
+         <database_version>2020-153</database_version>\
+         <date_of_creation>2020-06-01</date_of_creation>\
+         </header>\
+         {}\
+         </kanjidic2>",
+        characters
+    )
+}
+
+#[cfg(feature = "fs")]
 #[test]
 fn jmdict_works() {
     let cwd = env::current_dir().unwrap();
@@ -21,6 +59,7 @@ fn jmdict_works() {
         .collect();
 }
 
+#[cfg(feature = "fs")]
 #[test]
 fn kanjidic_works() {
     let cwd = env::current_dir().unwrap();
@@ -35,6 +74,2562 @@ fn kanjidic_works() {
         .collect();
 }
 
+#[test]
+fn kanjidic_radical_classifications() {
+    let dict = kanjidic_sample();
+    let entry = dict.find_literal("亜").unwrap();
+
+    let classical = entry
+        .radicals
+        .iter()
+        .find(|r| matches!(r.classification, RadicalType::Classical))
+        .unwrap();
+    assert_eq!(classical.number, 7);
+    assert_eq!(classical.value, "二");
+
+    let nelson = entry
+        .radicals
+        .iter()
+        .find(|r| matches!(r.classification, RadicalType::NelsonC))
+        .unwrap();
+    assert_eq!(nelson.number, 1);
+    assert_eq!(nelson.value, "一");
+}
+
+#[test]
+fn kanjidic_meaning_languages() {
+    let dict = kanjidic_sample();
+    assert_eq!(dict.meaning_languages(), vec!["en", "fr"]);
+}
+
+fn literals<'a>(entries: &[&'a Entry]) -> Vec<&'a str> {
+    entries.iter().map(|e| e.literal.as_str()).collect()
+}
+
+#[test]
+fn kanjidic_sorted_by() {
+    let dict = kanjidic_sample();
+
+    let by_freq = dict.sorted_by(SortKey::Frequency);
+    assert_eq!(literals(&by_freq), vec!["一", "水", "亜", "丂"]);
+
+    // 一 and 水 are both grade 1, so they fall back to codepoint order.
+    let by_grade = dict.sorted_by(SortKey::Grade);
+    assert_eq!(literals(&by_grade), vec!["一", "水", "亜", "丂"]);
+
+    let by_strokes = dict.sorted_by(SortKey::StrokeCount);
+    assert_eq!(literals(&by_strokes), vec!["一", "丂", "水", "亜"]);
+
+    let by_literal = dict.sorted_by(SortKey::Literal);
+    assert_eq!(literals(&by_literal), vec!["一", "丂", "亜", "水"]);
+    assert_eq!(
+        literals(&by_literal),
+        literals(&dict.sorted_by(SortKey::Literal))
+    );
+}
+
+#[test]
+fn kanjidic_sorted_by_cmp() {
+    let dict = kanjidic_sample();
+
+    // 亜 and 一 both have five readings, and keep their document order.
+    let by_readings = dict.sorted_by_cmp(|a, b| b.reading_count().cmp(&a.reading_count()));
+    assert_eq!(literals(&by_readings), vec!["亜", "一", "水", "丂"]);
+
+    let by_meanings = dict.sorted_by_cmp(|a, b| a.meaning_count().cmp(&b.meaning_count()));
+    assert_eq!(literals(&by_meanings), vec!["丂", "水", "一", "亜"]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn kanjidic_to_json() {
+    let mut dict = kanjidic_sample();
+    dict.entries.retain(|e| e.literal == "丂");
+
+    let expected = concat!(
+        r#"[{"codepoints":{"ucs":"4e02"},"freq":null,"grade":null,"jlpt":null,"#,
+        r#""korean_h":[],"korean_r":[],"kun":[],"literal":"丂","#,
+        r#""meanings":[],"nanori":[],"on":["コウ"],"#,
+        r#""pinyin":["kao3"],"radicals":{"classical":1},"stroke_count":2,"#,
+        r#""stroke_miscounts":[],"vietnam":[]}]"#,
+    );
+    assert_eq!(dict.to_json(), expected);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn kanjidic_entry_to_value() {
+    let dict = kanjidic_sample();
+    let value = dict.find_literal("亜").unwrap().to_value();
+
+    assert_eq!(value["on"][0], "ア");
+    assert_eq!(value["kun"][0], "つ.ぐ");
+    assert_eq!(value["radicals"]["nelson_c"], 1);
+    assert_eq!(value["meanings"][0]["value"], "Asia");
+    assert_eq!(value["meanings"][4]["lang"], "fr");
+    assert!(value["vietnam"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn kanjidic_lenient_unknown_reading_type() {
+    let contents = kanjidic_xml(
+        r#"<character>
+        <literal>水</literal>
+        <codepoint><cp_value cp_type="ucs">6c34</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">85</rad_value></radical>
+        <misc><stroke_count>4</stroke_count></misc>
+        <reading_meaning><rmgroup>
+        <reading r_type="ja_on">スイ</reading>
+        <reading r_type="cantonese">seoi2</reading>
+        <meaning>water</meaning>
+        </rmgroup></reading_meaning>
+        </character>"#,
+    );
+
+    assert!(contents.parse::<Kanjidic>().is_err());
+
+    let (dict, warnings) = Kanjidic::from_str_lenient(&contents).unwrap();
+    let readings = &dict.find_literal("水").unwrap().reading_meanings[0].readings;
+    assert_eq!(readings.len(), 2);
+    assert!(matches!(&readings[1].typ, ReadingType::Other(t) if t == "cantonese"));
+
+    assert_eq!(warnings.len(), 1);
+    assert!(matches!(warnings[0], ParseWarning::UnknownEnum(_)));
+}
+
+#[test]
+fn kana_conversion() {
+    let hiragana = "あいうえおかきくけこさしすせそたちつてとなにぬねのはひふへほ\
+                    まみむめもやゆよらりるれろわゐゑをん\
+                    がぎぐげござじずぜぞだぢづでどばびぶべぼぱぴぷぺぽゔ\
+                    ぁぃぅぇぉっゃゅょゎゝゞ";
+    let katakana = "アイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホ\
+                    マミムメモヤユヨラリルレロワヰヱヲン\
+                    ガギグゲゴザジズゼゾダヂヅデドバビブベボパピプペポヴ\
+                    ァィゥェォッャュョヮヽヾ";
+    assert_eq!(util::to_hiragana(katakana), hiragana);
+    assert_eq!(util::to_katakana(hiragana), katakana);
+
+    assert_eq!(util::to_hiragana("キョウ"), "きょう");
+    assert_eq!(util::to_katakana("しゅっ.ぱつ"), "シュッ.パツ");
+    assert_eq!(util::to_hiragana("コーヒー"), "こーひー");
+    assert_eq!(util::to_hiragana("ya4"), "ya4");
+
+    let dict = kanjidic_sample();
+    let readings = &dict.find_literal("亜").unwrap().reading_meanings[0].readings;
+    let on = readings.iter().find(|r| r.value == "ア").unwrap();
+    assert_eq!(on.normalized_kana(), "あ");
+}
+
+#[test]
+fn kanjidic_search_reading() {
+    let dict = kanjidic_with(
+        r#"<character>
+        <literal>食</literal>
+        <codepoint><cp_value cp_type="ucs">98df</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">184</rad_value></radical>
+        <misc><stroke_count>9</stroke_count></misc>
+        <reading_meaning><rmgroup>
+        <reading r_type="ja_on">ショク</reading>
+        <reading r_type="ja_kun">く.う</reading>
+        <reading r_type="ja_kun">た.べる</reading>
+        <meaning>eat</meaning>
+        </rmgroup></reading_meaning>
+        </character>"#,
+    );
+
+    assert_eq!(literals(&dict.search_reading("たべる")), vec!["食"]);
+    assert_eq!(literals(&dict.search_reading("た")), vec!["食"]);
+    assert_eq!(literals(&dict.search_reading("しょく")), vec!["食"]);
+    assert_eq!(literals(&dict.search_reading("タベル")), vec!["食"]);
+    assert!(dict.search_reading("たべ").is_empty());
+
+    let dict = kanjidic_sample();
+    assert_eq!(literals(&dict.search_reading("ひと")), vec!["一"]);
+    assert_eq!(literals(&dict.search_reading("かず")), vec!["一"]);
+}
+
+#[test]
+fn kanjidic_reading_okurigana() {
+    let reading = |value: &str, typ| Reading {
+        value: value.to_owned(),
+        typ,
+    };
+
+    let taberu = reading("た.べる", ReadingType::Kunyomi(None));
+    assert_eq!(taberu.stem(), "た");
+    assert_eq!(taberu.okurigana(), Some("べる"));
+    assert!(!taberu.is_prefix() && !taberu.is_suffix());
+
+    let sageru = reading("-さ.げる", ReadingType::Kunyomi(None));
+    assert_eq!(sageru.stem(), "さ");
+    assert_eq!(sageru.okurigana(), Some("げる"));
+    assert!(sageru.is_suffix() && !sageru.is_prefix());
+
+    let hito = reading("ひと-", ReadingType::Kunyomi(None));
+    assert_eq!(hito.stem(), "ひと");
+    assert_eq!(hito.okurigana(), None);
+    assert!(hito.is_prefix());
+
+    let on = reading("ショク", ReadingType::Onyomi(None, OnyomiType::None));
+    assert_eq!(on.stem(), "ショク");
+    assert_eq!(on.okurigana(), None);
+}
+
+#[test]
+fn kanjidic_moro_ref() {
+    let dict = kanjidic_sample();
+
+    let moro = dict.find_literal("亜").unwrap().moro().unwrap();
+    assert_eq!(moro.index, "272");
+    assert_eq!(moro.volume, Some(1));
+    assert_eq!(moro.page, Some(525));
+
+    assert!(dict.find_literal("一").unwrap().moro().is_none());
+}
+
+#[test]
+fn kanjidic_validate() {
+    assert!(kanjidic_sample().validate().is_empty());
+
+    let dict = kanjidic_with(
+        r#"<character>
+        <literal>水</literal>
+        <codepoint></codepoint>
+        <radical><rad_value rad_type="classical">85</rad_value></radical>
+        <misc>
+        <grade>1</grade>
+        <stroke_count>0</stroke_count>
+        <stroke_count>0</stroke_count>
+        </misc>
+        </character>"#,
+    );
+    let errors = dict.validate();
+    assert_eq!(errors.len(), 4);
+    assert!(matches!(&errors[0], ValidationError::ZeroStrokeCount(l) if l == "水"));
+    assert!(matches!(
+        errors[1],
+        ValidationError::InvalidStrokeMiscount(_, 0)
+    ));
+    assert!(matches!(errors[2], ValidationError::MissingCodepoints(_)));
+    assert!(matches!(errors[3], ValidationError::MissingReadings(_)));
+}
+
+#[test]
+fn kanjidic_reading_status() {
+    let dict = kanjidic_with(
+        r#"<character>
+        <literal>水</literal>
+        <codepoint><cp_value cp_type="ucs">6c34</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">85</rad_value></radical>
+        <misc><stroke_count>4</stroke_count></misc>
+        <reading_meaning><rmgroup>
+        <reading r_type="ja_on" r_status="jy">スイ</reading>
+        <reading r_type="ja_kun" r_status="xx">みず</reading>
+        <reading r_type="ja_kun">みず-</reading>
+        </rmgroup></reading_meaning>
+        </character>"#,
+    );
+    let readings = &dict.entries[0].reading_meanings[0].readings;
+
+    assert!(matches!(readings[0].status(), Some(ReadingStatus::Jouyou)));
+    assert!(readings[0].is_jouyou_approved());
+    assert!(matches!(readings[1].status(), Some(ReadingStatus::Other(s)) if s == "xx"));
+    assert!(!readings[1].is_jouyou_approved());
+    assert!(readings[2].status().is_none());
+}
+
+#[test]
+fn kanjidic_stats() {
+    let dict = kanjidic_sample();
+    let stats = dict.stats();
+
+    assert_eq!(stats.total_entries, dict.entries.len());
+    assert_eq!(
+        stats.by_stroke_count.values().sum::<usize>(),
+        dict.entries.len()
+    );
+
+    let ungraded = dict.entries.iter().filter(|e| e.grade.is_none()).count();
+    assert_eq!(
+        stats.by_grade.values().sum::<usize>() + ungraded,
+        dict.entries.len()
+    );
+    assert_eq!(stats.by_grade[&1], 2);
+
+    assert_eq!(stats.with_freq, 3);
+    assert_eq!(stats.jlpt_distribution[&4], 2);
+}
+
+#[cfg(feature = "legacy")]
+const KANJIDIC_LEGACY_SAMPLE: &str = "\
+＃ KANJIDIC JIS X 0208 Kanji File
+亜 3021 U4e9c B1 C7 G8 S7 XJ13049 F1509 J1 N43 V81 H3540 L1809 MN272 MP1.0525 P4-7-1 Ya4 Wa ア つ.ぐ T1 や つぎ {Asia} {rank next} {come after} {-ous}
+一 306C U4e00 B1 G1 S1 F2 J4 N1 L1 P4-1-4 Yyi1 イチ イツ ひと- ひと.つ T1 かず T2 いち {one} {one radical (no.1)}
+";
+
+#[cfg(feature = "legacy")]
+#[test]
+fn kanjidic_legacy_format() {
+    use crate::kanjidic::DicRef;
+
+    let check = |dict: &Kanjidic| {
+        assert_eq!(dict.entries.len(), 2);
+
+        let a = dict.find_literal("亜").unwrap();
+        assert_eq!(a.codepoints[0].value, "1-16-01");
+        assert_eq!(a.codepoints[1].value, "4e9c");
+        assert!(matches!(
+            a.radicals[0].classification,
+            RadicalType::Classical
+        ));
+        assert_eq!(a.radicals[0].number, 7);
+        assert!(matches!(a.radicals[1].classification, RadicalType::NelsonC));
+        assert_eq!(a.radicals[1].number, 1);
+        assert_eq!(a.grade.as_ref().map(|g| g.number()), Some(8));
+        assert_eq!(a.stroke_count, 7);
+        assert_eq!(a.freq, Some(1509));
+        assert_eq!(a.old_jlpt, Some(1));
+        assert!(matches!(&a.dic_refs[0], DicRef::NelsonC(n) if n == "43"));
+        let moro = a.moro().unwrap();
+        assert_eq!(
+            (moro.index.as_str(), moro.volume, moro.page),
+            ("272", Some(1), Some(525))
+        );
+
+        let rm = &a.reading_meanings[0];
+        let values: Vec<_> = rm.readings.iter().map(|r| r.value.as_str()).collect();
+        assert_eq!(values, vec!["a4", "a", "ア", "つ.ぐ"]);
+        assert!(matches!(rm.readings[2].typ, ReadingType::Onyomi(..)));
+        assert!(matches!(rm.readings[3].typ, ReadingType::Kunyomi(..)));
+        assert_eq!(a.nanori_readings, vec!["や", "つぎ"]);
+        assert_eq!(rm.meanings.len(), 4);
+        assert_eq!(rm.meanings[1].content, "rank next");
+
+        let one = dict.find_literal("一").unwrap();
+        assert_eq!(one.codepoints[0].value, "1-16-76");
+        assert_eq!(one.radicals.len(), 1);
+        assert_eq!(one.nanori_readings, vec!["かず"]);
+        assert_eq!(one.rad_names, vec!["いち"]);
+    };
+
+    check(&Kanjidic::from_legacy_str(KANJIDIC_LEGACY_SAMPLE).unwrap());
+
+    #[cfg(feature = "fs")]
+    {
+        let (bytes, _, _) = encoding_rs::EUC_JP.encode(KANJIDIC_LEGACY_SAMPLE);
+        let path = env::temp_dir().join("jmdict_kanjidic_legacy_sample");
+        std::fs::write(&path, &bytes).unwrap();
+        check(&Kanjidic::from_legacy_file(path.to_str().unwrap()).unwrap());
+    }
+}
+
+#[cfg(feature = "legacy")]
+#[test]
+fn kanjidic_legacy_212() {
+    // The first kanji of JIS X 0212, at 1-16-01.
+    let line = "丂 3021 U4e02 B1 S2 Yqiao3 コウ キョウ {obstruction of breath}";
+    let dict = Kanjidic::from_legacy_str_212(line).unwrap();
+    let kanji = dict.find_literal("丂").unwrap();
+    assert_eq!(kanji.codepoint("jis212"), Some("1-16-01"));
+    assert_eq!(kanji.codepoint("jis208"), None);
+    assert_eq!(kanji.ucs_value(), Some(0x4e02));
+
+    let dict = Kanjidic::from_legacy_str(KANJIDIC_LEGACY_SAMPLE).unwrap();
+    assert!(dict
+        .entries
+        .iter()
+        .all(|e| e.codepoints[0].standard == "jis208"));
+
+    #[cfg(feature = "fs")]
+    {
+        // encoding_rs only encodes JIS X 0208, so the three-byte EUC-JP form
+        // of 丂 is written by hand.
+        let bytes = b"\x8f\xb0\xa1 3021 U4e02 B1 S2";
+        let path = env::temp_dir().join("jmdict_kanjd212_legacy_sample");
+        std::fs::write(&path, &bytes[..]).unwrap();
+        let dict = Kanjidic::from_legacy_file_212(path.to_str().unwrap()).unwrap();
+        assert_eq!(dict.entries[0].literal, "丂");
+        assert_eq!(dict.entries[0].codepoints[0].standard, "jis212");
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[cfg(feature = "legacy")]
+#[test]
+fn kanjidic_legacy_non_ascii_field() {
+    let dict = Kanjidic::from_legacy_str("亜 3021 U4e9c B1 G8 S7 ア ＝ Dア {Asia}\n").unwrap();
+    let kanji = dict.find_literal("亜").unwrap();
+    assert_eq!(kanji.onyomi(), vec!["ア"]);
+    assert_eq!(kanji.stroke_count, 7);
+    assert_eq!(kanji.primary_meaning(), Some("Asia"));
+}
+
+#[cfg(feature = "legacy")]
+#[test]
+fn kanjidic_legacy_bad_jis_code() {
+    assert!(Kanjidic::from_legacy_str("亜 3021 B1 S7").is_ok());
+    for jis in &["10", "2010", "3080", "7F21", "13021"] {
+        let line = format!("亜 {} B1 S7", jis);
+        match Kanjidic::from_legacy_str(&line) {
+            Err(ParseError::ParseString(ref s)) => assert!(s.contains(jis), "{}", s),
+            other => panic!("expected invalid JIS code for {}, got {:?}", jis, other),
+        }
+    }
+}
+
+#[test]
+fn kanjidic_batch_lookup() {
+    let dict = kanjidic_sample();
+
+    let found = dict.find_literals(&["水", "木", "一"]);
+    assert_eq!(found.len(), 3);
+    assert_eq!(found[0].unwrap().literal, "水");
+    assert!(found[1].is_none());
+    assert_eq!(found[2].unwrap().literal, "一");
+
+    let found = dict.find_in_text("一つの水と、もう一つの水。");
+    assert_eq!(literals(&found), vec!["一", "水"]);
+}
+
+#[test]
+fn kanjidic_entry_display() {
+    let dict = kanjidic_sample();
+    let entry = dict.find_literal("一").unwrap();
+
+    assert_eq!(entry.onyomi(), vec!["イチ", "イツ"]);
+    assert_eq!(entry.kunyomi(), vec!["ひと-", "ひと.つ"]);
+    assert_eq!(entry.primary_meaning(), Some("one"));
+
+    let expected = "一
+Strokes: 1
+Grade: Grade 1 (Kyōiku)
+On: イチ, イツ
+Kun: ひと-, ひと.つ
+Meanings: one; one radical (no.1)";
+    assert_eq!(entry.to_string(), expected);
+
+    let entry = dict.find_literal("亜").unwrap();
+    assert!(entry.to_string().contains("\nGrade: Jōyō\n"));
+}
+
+#[test]
+fn kanjidic_unique_readings() {
+    let dict = kanjidic_with(
+        r#"<character>
+        <literal>行</literal>
+        <codepoint><cp_value cp_type="ucs">884c</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">144</rad_value></radical>
+        <misc><stroke_count>6</stroke_count></misc>
+        <reading_meaning>
+        <rmgroup>
+        <reading r_type="ja_on">コウ</reading>
+        <reading r_type="ja_on">ギョウ</reading>
+        <reading r_type="ja_kun">い.く</reading>
+        <meaning>going</meaning>
+        </rmgroup>
+        <rmgroup>
+        <reading r_type="ja_on">アン</reading>
+        <reading r_type="ja_on">コウ</reading>
+        <reading r_type="ja_kun">い.く</reading>
+        <reading r_type="ja_kun">おこな.う</reading>
+        <meaning>carry out</meaning>
+        </rmgroup>
+        </reading_meaning>
+        </character>"#,
+    );
+    let entry = &dict.entries[0];
+
+    assert_eq!(entry.onyomi().len(), 4);
+    assert_eq!(entry.unique_onyomi(), vec!["コウ", "ギョウ", "アン"]);
+    assert_eq!(entry.unique_kunyomi(), vec!["い.く", "おこな.う"]);
+}
+
+#[test]
+fn kanjidic_missing_tag_position() {
+    let contents = "<kanjidic2>
+<header>
+<file_version>4</file_version>
+<database_version>2020-153</database_version>
+<date_of_creation>2020-06-01</date_of_creation>
+</header>
+  <character>
+    <literal>水</literal>
+    <codepoint><cp_value cp_type=\"ucs\">6c34</cp_value></codepoint>
+    <radical><rad_value rad_type=\"classical\">85</rad_value></radical>
+  </character>
+</kanjidic2>";
+
+    match contents.parse::<Kanjidic>() {
+        Err(ParseError::Xml(XmlError::MissingTag { tag, pos })) => {
+            assert_eq!(tag, "misc");
+            let pos = pos.unwrap();
+            assert_eq!((pos.row, pos.col), (7, 3));
+        }
+        other => panic!("expected missing tag error, got {:?}", other),
+    }
+}
+
+#[test]
+fn kanjidic_no_stroke_counts() {
+    let xml = kanjidic_xml(
+        r#"<character>
+        <literal>水</literal>
+        <codepoint><cp_value cp_type="ucs">6c34</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">85</rad_value></radical>
+        <misc><grade>1</grade></misc>
+        </character>"#,
+    );
+    match xml.parse::<Kanjidic>() {
+        Err(ParseError::Xml(XmlError::MissingTag { tag, .. })) => assert_eq!(tag, "stroke_count"),
+        other => panic!("expected missing tag error, got {:?}", other),
+    }
+}
+
+#[test]
+fn kanjidic_group_by_grade() {
+    let dict = kanjidic_sample();
+    let groups = dict.group_by_grade();
+
+    let grades: Vec<_> = groups.keys().copied().collect();
+    assert_eq!(grades, vec![Grade::Kyouiku(1), Grade::Jouyou]);
+    assert_eq!(literals(&groups[&Grade::Kyouiku(1)]), vec!["一", "水"]);
+    assert!(groups.values().all(|g| !g.is_empty()));
+    assert_eq!(literals(&dict.ungraded()), vec!["丂"]);
+}
+
+#[test]
+fn kanjidic_by_radical() {
+    let dict = kanjidic_sample();
+
+    assert_eq!(literals(&dict.by_radical(85)), vec!["水"]);
+    assert_eq!(literals(&dict.by_radical_char('水')), vec!["水"]);
+    assert_eq!(literals(&dict.by_radical(1)), vec!["一", "丂"]);
+    assert_eq!(
+        literals(&dict.by_radical_in(RadicalType::NelsonC, 1)),
+        vec!["亜"]
+    );
+    assert!(dict.by_radical_char('氵').is_empty());
+}
+
+#[test]
+fn kanjidic_literal_char() {
+    let dict = kanjidic_sample();
+    let chars: Vec<_> = dict.entries.iter().map(|e| e.as_char()).collect();
+    assert_eq!(chars, vec![Some('亜'), Some('一'), Some('水'), Some('丂')]);
+
+    let xml = kanjidic_xml(
+        r#"<character>
+        <literal>水水</literal>
+        <codepoint><cp_value cp_type="ucs">6c34</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">85</rad_value></radical>
+        <misc><stroke_count>4</stroke_count></misc>
+        </character>"#,
+    );
+    let err = xml.parse::<Kanjidic>().unwrap_err();
+    assert!(matches!(err, ParseError::ParseString(ref s) if s.contains("水水")));
+
+    // Entries with an emptied literal are skipped rather than panicking.
+    let mut dict = kanjidic_sample();
+    dict.entries[0].literal.clear();
+    assert_eq!(dict.entries[0].as_char(), None);
+    assert!(!dict.entries[0].is_kanji());
+    dict.attach_kradfile(&"亜 : ｜ 一 口".parse().unwrap());
+    assert!(dict.entries[0].components().is_empty());
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn kanjidic_merge_files() {
+    let dir = env::temp_dir();
+    let first = dir.join("jmdict_merge_first.xml");
+    let second = dir.join("jmdict_merge_second.xml");
+    fs::write(&first, KANJIDIC_SAMPLE).unwrap();
+    fs::write(
+        &second,
+        kanjidic_xml(
+            r#"<character>
+            <literal>水</literal>
+            <codepoint><cp_value cp_type="ucs">6c34</cp_value></codepoint>
+            <radical><rad_value rad_type="classical">85</rad_value></radical>
+            <misc><stroke_count>5</stroke_count></misc>
+            </character>
+            <character>
+            <literal>丄</literal>
+            <codepoint><cp_value cp_type="ucs">4e04</cp_value></codepoint>
+            <radical><rad_value rad_type="classical">1</rad_value></radical>
+            <misc><stroke_count>2</stroke_count></misc>
+            </character>"#,
+        ),
+    )
+    .unwrap();
+
+    let dict = Kanjidic::from_files(&[first.to_str().unwrap(), second.to_str().unwrap()]).unwrap();
+    let entries: Vec<_> = dict.entries.iter().collect();
+    assert_eq!(literals(&entries), vec!["亜", "一", "水", "丂", "丄"]);
+    assert_eq!(dict.find_literal("水").unwrap().stroke_count, 5);
+    assert_eq!(dict.header.file_version, 4);
+
+    fs::remove_file(first).unwrap();
+    fs::remove_file(second).unwrap();
+    assert!(Kanjidic::from_files(&[]).is_err());
+}
+
+#[test]
+fn kanjidic_by_jlpt() {
+    let dict = kanjidic_sample();
+
+    let elementary = dict.by_jlpt(4);
+    assert_eq!(literals(&elementary), vec!["一", "水"]);
+    assert!(elementary
+        .iter()
+        .all(|e| matches!(e.grade, Some(Grade::Kyouiku(1)))));
+    assert_eq!(literals(&dict.by_jlpt(1)), vec!["亜"]);
+    assert_eq!(dict.find_literal("丂").unwrap().jlpt_level(), None);
+}
+
+#[test]
+fn kanjidic_default_meaning_lang() {
+    let options = ParseOptions {
+        default_meaning_lang: "fr".to_owned(),
+        ..ParseOptions::default()
+    };
+    let (dict, warnings) = Kanjidic::from_str_with_options(KANJIDIC_SAMPLE, options).unwrap();
+    assert!(warnings.is_empty());
+
+    let water = dict.find_literal("水").unwrap();
+    let langs: Vec<_> = water.reading_meanings[0]
+        .meanings
+        .iter()
+        .map(|m| (m.content.as_str(), &*m.language))
+        .collect();
+    assert_eq!(langs, vec![("water", "fr"), ("eau", "fr")]);
+
+    let water = kanjidic_sample();
+    let water = water.find_literal("水").unwrap();
+    assert_eq!(&*water.reading_meanings[0].meanings[0].language, "en");
+}
+
+#[test]
+fn kanjidic_codepoint_standard() {
+    let dict = kanjidic_sample();
+
+    assert!(dict.find_literal("水").unwrap().has_codepoint("jis208"));
+    assert!(!dict.find_literal("丂").unwrap().has_codepoint("jis208"));
+    assert_eq!(
+        literals(&dict.with_codepoint_standard("jis208")),
+        vec!["亜", "一", "水"]
+    );
+    assert_eq!(dict.with_codepoint_standard("ucs").len(), 4);
+}
+
+#[test]
+fn kanjidic_dic_ref_kinds() {
+    for &kind in DicRefKind::ALL.iter() {
+        assert_eq!(kind.as_str().parse::<DicRefKind>().unwrap(), kind);
+    }
+    assert_eq!(DicRefKind::all_types().len(), DicRefKind::ALL.len());
+    assert!("nelson".parse::<DicRefKind>().is_err());
+
+    let dict = kanjidic_sample();
+    let kinds: Vec<_> = dict
+        .find_literal("亜")
+        .unwrap()
+        .dic_refs
+        .iter()
+        .map(DicRef::kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            Some(DicRefKind::NelsonC),
+            Some(DicRefKind::Heisig),
+            Some(DicRefKind::Moro)
+        ]
+    );
+}
+
+#[test]
+fn kanjidic_by_dic_ref() {
+    let dict = kanjidic_sample();
+
+    let heisig = dict.by_dic_ref(DicRefKind::Heisig, "137").unwrap();
+    assert_eq!(heisig.literal, "水");
+    assert_eq!(
+        dict.by_dic_ref(DicRefKind::Moro, "272").unwrap().literal,
+        "亜"
+    );
+    assert!(dict.by_dic_ref(DicRefKind::Heisig, "13").is_none());
+    assert!(dict.by_dic_ref(DicRefKind::NelsonN, "137").is_none());
+
+    let index = dict.dic_ref_index(DicRefKind::NelsonC);
+    assert_eq!(index.len(), 3);
+    assert_eq!(index["2482"].literal, "水");
+}
+
+#[test]
+fn parse_error_source_chain() {
+    use std::error::Error;
+
+    let err = "<kanjidic2><header></kanjidic2>"
+        .parse::<Kanjidic>()
+        .unwrap_err();
+    let root = err.source().and_then(|e| e.source()).unwrap();
+    assert!(root.is::<roxmltree::Error>());
+    assert!(err.to_string().starts_with("XML error: roxmltree error: "));
+    assert!(err.to_string().ends_with(&root.to_string()));
+
+    let err = kanjidic_xml("<character><literal>水</literal></character>")
+        .parse::<Kanjidic>()
+        .unwrap_err();
+    assert!(err.source().unwrap().is::<XmlError>());
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn kanjidic_filtered() {
+    let wanted = ["水", "一", "丄"];
+    let dict = Kanjidic::from_str_filtered(KANJIDIC_SAMPLE, |l| wanted.contains(&l)).unwrap();
+    let entries: Vec<_> = dict.entries.iter().collect();
+    assert_eq!(literals(&entries), vec!["一", "水"]);
+
+    let path = env::temp_dir().join("jmdict_filtered.xml");
+    fs::write(&path, KANJIDIC_SAMPLE).unwrap();
+    let dict = Kanjidic::from_file_filtered(path.to_str().unwrap(), |l| l == "亜").unwrap();
+    fs::remove_file(path).unwrap();
+    assert_eq!(dict.entries.len(), 1);
+    assert_eq!(dict.header.file_version, 4);
+}
+
+#[test]
+fn kanjidic_clone_entry() {
+    let dict = kanjidic_sample();
+    let original = dict.find_literal("水").unwrap();
+
+    let mut copy = original.clone();
+    copy.stroke_count = 5;
+    copy.nanori_readings.push("ながれ".to_owned());
+    copy.reading_meanings[0].meanings.clear();
+
+    assert_eq!(original.stroke_count, 4);
+    assert_eq!(original.nanori_readings, vec!["み"]);
+    assert_eq!(original.reading_meanings[0].meanings.len(), 2);
+}
+
+#[test]
+fn kanjidic_into_entries() {
+    let dict = kanjidic_sample();
+    let len = dict.entries.len();
+
+    let entries = dict.into_entries();
+    assert_eq!(entries.len(), len);
+    assert_eq!(entries[0].literal, "亜");
+}
+
+#[test]
+fn kanjidic_search_by_reading_type() {
+    let dict = kanjidic_sample();
+
+    assert_eq!(literals(&dict.search_onyomi("スイ")), vec!["水"]);
+    assert_eq!(literals(&dict.search_onyomi("すい")), vec!["水"]);
+    assert!(dict.search_onyomi("みず").is_empty());
+
+    assert_eq!(literals(&dict.search_kunyomi("ミズ")), vec!["水"]);
+    assert_eq!(literals(&dict.search_kunyomi("つぐ")), vec!["亜"]);
+    assert!(dict.search_kunyomi("すい").is_empty());
+}
+
+#[cfg(feature = "compact")]
+#[test]
+fn kanjidic_compact() {
+    use crate::kanjidic::CompactKanjidic;
+    use std::sync::Arc;
+
+    let dict = kanjidic_sample();
+    let compact = CompactKanjidic::from(&dict);
+    assert_eq!(compact.entries().len(), dict.entries.len());
+
+    for e in &dict.entries {
+        let c = compact.find_literal(&e.literal).unwrap();
+        assert_eq!(Some(c.literal), e.as_char());
+        assert_eq!(c.stroke_count, e.stroke_count);
+        assert_eq!(c.grade, e.grade);
+        let onyomi: Vec<_> = c.onyomi.iter().map(|r| &**r).collect();
+        assert_eq!(onyomi, e.onyomi());
+        let kunyomi: Vec<_> = c.kunyomi.iter().map(|r| &**r).collect();
+        assert_eq!(kunyomi, e.kunyomi());
+    }
+
+    for query in &["みず", "ひと", "つぐ", "スイ", "や"] {
+        let expected = literals(&dict.search_reading(query));
+        let found: Vec<_> = compact
+            .search_reading(query)
+            .iter()
+            .map(|e| e.literal.to_string())
+            .collect();
+        assert_eq!(found, expected);
+    }
+    assert_eq!(compact.by_jlpt(4).len(), dict.by_jlpt(4).len());
+    assert_eq!(
+        compact.filter_meaning(|m| &*m.language == "fr").len(),
+        dict.filter_meaning(|m| &*m.language == "fr").len()
+    );
+
+    let asia = compact.find_char('亜').unwrap();
+    assert!(Arc::ptr_eq(
+        &asia.meanings[0].language,
+        &compact.find_char('水').unwrap().meanings[0].language
+    ));
+
+    let mut emptied = dict.clone();
+    emptied.entries[0].literal.clear();
+    let compact = CompactKanjidic::from(&emptied);
+    assert_eq!(compact.entries().len(), dict.entries.len() - 1);
+    assert!(compact.find_char('亜').is_none());
+}
+
+#[test]
+fn kanjidic_variants() {
+    let dict = kanjidic_with(
+        r#"<character>
+        <literal>亜</literal>
+        <codepoint><cp_value cp_type="jis208">1-16-01</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">7</rad_value></radical>
+        <misc><stroke_count>7</stroke_count>
+        <variant var_type="jis208">1-48-19</variant>
+        <variant var_type="nelson_c">44</variant></misc>
+        </character>
+        <character>
+        <literal>亞</literal>
+        <codepoint><cp_value cp_type="jis208">1-48-19</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">7</rad_value></radical>
+        <misc><stroke_count>8</stroke_count>
+        <variant var_type="jis208">1-16-01</variant></misc>
+        </character>"#,
+    );
+
+    let new_form = dict.find_literal("亜").unwrap();
+    let variants: Vec<_> = new_form
+        .variants
+        .iter()
+        .map(|v| (v.typ.as_str(), v.value.as_str()))
+        .collect();
+    assert_eq!(variants, vec![("jis208", "1-48-19"), ("nelson_c", "44")]);
+    assert_eq!(literals(&dict.variants_of(new_form)), vec!["亞"]);
+
+    let old_form = dict.find_literal("亞").unwrap();
+    assert_eq!(literals(&dict.variants_of(old_form)), vec!["亜"]);
+    assert!(kanjidic_sample()
+        .entries
+        .iter()
+        .all(|e| e.variants.is_empty()));
+}
+
+#[test]
+fn kanjidic_rad_names() {
+    let dict = kanjidic_with(
+        r#"<character>
+        <literal>亠</literal>
+        <codepoint><cp_value cp_type="ucs">4ea0</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">8</rad_value></radical>
+        <misc><stroke_count>2</stroke_count>
+        <rad_name>なべぶた</rad_name>
+        <rad_name>けいさんかんむり</rad_name></misc>
+        </character>"#,
+    );
+
+    let lid = dict.find_literal("亠").unwrap();
+    assert_eq!(lid.rad_names, vec!["なべぶた", "けいさんかんむり"]);
+    assert!(kanjidic_sample()
+        .entries
+        .iter()
+        .all(|e| e.rad_names.is_empty()));
+}
+
+#[cfg(feature = "streaming")]
+#[test]
+fn kanjidic_parse_streaming() {
+    let mut count = 0;
+    let mut strokes = Vec::new();
+    Kanjidic::parse_streaming(KANJIDIC_SAMPLE.as_bytes(), |e| {
+        count += 1;
+        strokes.push((e.literal, e.stroke_count));
+    })
+    .unwrap();
+
+    assert_eq!(count, kanjidic_sample().entries.len());
+    assert_eq!(strokes[2], ("水".to_owned(), 4));
+
+    let broken = kanjidic_xml("<character><literal>水</literal></character>");
+    assert!(Kanjidic::parse_streaming(broken.as_bytes(), |_| {}).is_err());
+}
+
+#[test]
+fn kanjidic_diff() {
+    let old = kanjidic_sample();
+    let new = KANJIDIC_SAMPLE
+        // Reordered readings and meanings don't count as a change.
+        .replace(
+            "<reading r_type=\"ja_kun\">みず</reading>\n<reading r_type=\"ja_kun\">みず-</reading>",
+            "<reading r_type=\"ja_kun\">みず-</reading>\n<reading r_type=\"ja_kun\">みず</reading>",
+        )
+        .replace(
+            "<meaning>water</meaning>\n<meaning m_lang=\"fr\">eau</meaning>",
+            "<meaning m_lang=\"fr\">eau</meaning>\n<meaning>water</meaning>",
+        )
+        .replace("<freq>1509</freq>", "<freq>1510</freq>")
+        .replace("<literal>丂</literal>", "<literal>丄</literal>");
+    let new: Kanjidic = new.parse().unwrap();
+
+    let water = (old.find_literal("水"), new.find_literal("水"));
+    assert!(water.0.unwrap().content_eq(water.1.unwrap()));
+    assert!(!old.entries[0].content_eq(&new.entries[0]));
+
+    let diff = old.diff(&new);
+    assert_eq!(diff.added, vec!["丄"]);
+    assert_eq!(diff.removed, vec!["丂"]);
+    assert_eq!(diff.changed, vec!["亜"]);
+    assert!(old.diff(&kanjidic_sample()).changed.is_empty());
+}
+
+#[test]
+fn kanjidic_onyomi_type() {
+    let reading = |attrs: &str| {
+        kanjidic_xml(&format!(
+            r#"<character>
+            <literal>水</literal>
+            <codepoint><cp_value cp_type="ucs">6c34</cp_value></codepoint>
+            <radical><rad_value rad_type="classical">85</rad_value></radical>
+            <misc><stroke_count>4</stroke_count></misc>
+            <reading_meaning><rmgroup>
+            <reading r_type="ja_on"{}>スイ</reading>
+            </rmgroup></reading_meaning>
+            </character>"#,
+            attrs
+        ))
+        .parse::<Kanjidic>()
+        .map(|d| d.entries[0].reading_meanings[0].readings[0].typ.clone())
+    };
+
+    assert!(matches!(
+        reading("").unwrap(),
+        ReadingType::Onyomi(None, OnyomiType::None)
+    ));
+    assert!(matches!(
+        reading(r#" on_type="kan'you""#).unwrap(),
+        ReadingType::Onyomi(None, OnyomiType::Kanyou)
+    ));
+    assert!(matches!(
+        reading(r#" on_type="wei""#),
+        Err(ParseError::ParseEnum(_))
+    ));
+}
+
+#[test]
+fn kanjidic_query() {
+    let dict = kanjidic_sample();
+
+    let found = dict
+        .query()
+        .grade(Grade::Kyouiku(1))
+        .max_strokes(3)
+        .has_meaning("One")
+        .collect();
+    assert_eq!(literals(&found), vec!["一"]);
+
+    let found = dict
+        .query()
+        .min_strokes(2)
+        .radical(1)
+        .filter(|e| e.grade.is_none())
+        .collect();
+    assert_eq!(literals(&found), vec!["丂"]);
+
+    let found = dict
+        .query()
+        .jlpt(4)
+        .reading("みず")
+        .max_strokes(4)
+        .collect();
+    assert_eq!(literals(&found), vec!["水"]);
+    assert!(dict
+        .query()
+        .jlpt(4)
+        .has_meaning("eau")
+        .max_strokes(3)
+        .collect()
+        .is_empty());
+    assert_eq!(dict.query().collect().len(), dict.entries.len());
+}
+
+#[test]
+fn kanjidic_is_newer_than() {
+    let cached = kanjidic_with("");
+    let with_header = |version: &str, date: &str| {
+        let mut dict = cached.clone();
+        dict.header.database_version = version.to_owned();
+        dict.header.creation_date = date.to_owned();
+        dict
+    };
+
+    let later_day = with_header("2020-154", "2020-06-02");
+    assert!(later_day.is_newer_than(&cached));
+    assert!(!cached.is_newer_than(&later_day));
+    assert!(!cached.is_newer_than(&cached));
+
+    // 2021-5 is newer than 2020-153 even though "5" sorts before "153".
+    assert!(with_header("2021-5", "2021-01-05").is_newer_than(&cached));
+
+    // The dates break ties between equal versions and stand in for unparseable
+    // ones.
+    assert!(with_header("2020-153", "2020-06-10").is_newer_than(&cached));
+    assert!(with_header("unknown", "2020-7-1").is_newer_than(&cached));
+    assert!(!with_header("unknown", "unknown").is_newer_than(&cached));
+}
+
+#[test]
+fn kanjidic_header() {
+    let header = kanjidic_sample().header;
+    assert_eq!(
+        header,
+        Header {
+            file_version: 4,
+            database_version: "2020-153".to_owned(),
+            creation_date: "2020-06-01".to_owned(),
+        }
+    );
+
+    #[cfg(feature = "chrono")]
+    {
+        let date = chrono::NaiveDate::from_ymd_opt(2020, 6, 1);
+        assert_eq!(header.date_of_creation(), date);
+
+        let mut header = header;
+        for raw in &["2020-6-1", "2020-06-31", "June 2020", "2020-06-01-"] {
+            header.creation_date = raw.to_string();
+            assert_eq!(header.date_of_creation(), None, "{}", raw);
+        }
+    }
+}
+
+#[test]
+fn kanjidic_supplementary_plane() {
+    let dict = kanjidic_with(
+        r#"<character>
+        <literal>𠀋</literal>
+        <codepoint><cp_value cp_type="ucs">2000b</cp_value>
+        <cp_value cp_type="jis213">1-14-2</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">1</rad_value></radical>
+        <misc><stroke_count>6</stroke_count></misc>
+        </character>
+        <character>
+        <literal>水</literal>
+        <codepoint><cp_value cp_type="ucs">6c34</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">85</rad_value></radical>
+        <misc><stroke_count>4</stroke_count></misc>
+        </character>"#,
+    );
+
+    let e = dict.find_char('\u{2000B}').unwrap();
+    assert_eq!(e.literal.len(), 4);
+    assert_eq!(e.literal.chars().count(), 1);
+    assert_eq!(e.as_char(), Some('𠀋'));
+    assert_eq!(e.ucs_value(), Some(0x2000B));
+    assert_eq!(std::char::from_u32(e.ucs_value().unwrap()), e.as_char());
+    assert!(dict.find_literal("𠀋").is_some());
+    assert_eq!(literals(&dict.find_in_text("𠀋と水と𠀋")), vec!["𠀋", "水"]);
+    assert!(dict.find_char('𠀌').is_none());
+}
+
+#[test]
+fn kanjidic_stroke_counts() {
+    let strokes = |counts: &str| {
+        kanjidic_xml(&format!(
+            r#"<character>
+            <literal>亜</literal>
+            <codepoint><cp_value cp_type="ucs">4e9c</cp_value></codepoint>
+            <radical><rad_value rad_type="classical">7</rad_value></radical>
+            <misc>{}</misc>
+            </character>"#,
+            counts
+        ))
+        .parse::<Kanjidic>()
+    };
+
+    let dict = strokes("<stroke_count>7</stroke_count>").unwrap();
+    assert_eq!(dict.entries[0].all_stroke_counts(), vec![7]);
+    assert!(dict.entries[0].stroke_miscounts.is_empty());
+
+    let dict = strokes(
+        "<stroke_count>7</stroke_count><stroke_count>8</stroke_count>\
+         <stroke_count>6</stroke_count>",
+    )
+    .unwrap();
+    assert_eq!(dict.entries[0].stroke_count, 7);
+    assert_eq!(dict.entries[0].all_stroke_counts(), vec![7, 8, 6]);
+
+    let err = strokes("<grade>8</grade>").unwrap_err();
+    assert!(matches!(
+        err,
+        ParseError::Xml(XmlError::MissingTag { ref tag, .. }) if tag == "stroke_count"
+    ));
+}
+
+#[test]
+fn kanjidic_entry_iterators() {
+    let dict = kanjidic_sample();
+    let a = dict.find_literal("亜").unwrap();
+
+    assert_eq!(a.readings().count(), 5);
+    assert_eq!(a.meanings().count(), 6);
+    assert_eq!(a.readings().nth(3).unwrap().value, "ア");
+    assert_eq!(a.meanings().last().unwrap().content, "suivant");
+    assert_eq!(dict.find_literal("丂").unwrap().meanings().count(), 0);
+}
+
+#[cfg(feature = "fuzzy")]
+#[test]
+fn kanjidic_search_meaning_fuzzy() {
+    let dict = kanjidic_sample();
+
+    let found: Vec<_> = dict
+        .search_meaning_fuzzy("watr", 1)
+        .into_iter()
+        .map(|(e, d)| (e.literal.as_str(), d))
+        .collect();
+    assert_eq!(found, vec![("水", 1)]);
+
+    let found: Vec<_> = dict
+        .search_meaning_fuzzy("One", 2)
+        .into_iter()
+        .map(|(e, d)| (e.literal.as_str(), d))
+        .collect();
+    assert_eq!(found, vec![("一", 0), ("亜", 2)]);
+    assert!(dict.search_meaning_fuzzy("watr", 0).is_empty());
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn kanjidic_missing_file() {
+    let path = env::temp_dir().join("jmdict_does_not_exist.xml");
+    let err = Kanjidic::from_file(path.to_str().unwrap()).unwrap_err();
+
+    match err {
+        ParseError::Io {
+            path: Some(ref p),
+            ref source,
+        } => {
+            assert_eq!(p, &path);
+            assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+        }
+        ref err => panic!("unexpected error: {:?}", err),
+    }
+    assert!(err.to_string().contains("jmdict_does_not_exist.xml"));
+}
+
+#[test]
+fn kanjidic_to_anki_tsv() {
+    use crate::kanjidic::AnkiField;
+
+    let mut dict = kanjidic_sample();
+    dict.entries.retain(|e| e.literal != "亜");
+    dict.entries[1].reading_meanings[0].meanings[0].content = "water\t\"mizu\"".to_owned();
+
+    let fields = [
+        AnkiField::Literal,
+        AnkiField::StrokeCount,
+        AnkiField::Onyomi,
+        AnkiField::Kunyomi,
+        AnkiField::Meanings,
+        AnkiField::Grade,
+    ];
+    let expected = "\
+一\t1\tイチ, イツ\tひと-, ひと.つ\tone; one radical (no.1)\t1
+水\t4\tスイ\tみず, みず-\t\"water\t\"\"mizu\"\"\"\t1
+丂\t2\tコウ\t\t\t
+";
+    assert_eq!(dict.to_anki_tsv(&fields), expected);
+}
+
+#[test]
+fn kanjidic_grade_order() {
+    assert!(Grade::Kyouiku(3) < Grade::Jouyou);
+    assert!(Grade::Kyouiku(6) < Grade::Jouyou);
+    assert!(Grade::Jouyou < Grade::Jinmeiyou);
+
+    let mut grades = vec![
+        Grade::JouyouVariant,
+        Grade::Kyouiku(6),
+        Grade::Jinmeiyou,
+        Grade::Jouyou,
+        Grade::Kyouiku(1),
+        Grade::Kyouiku(3),
+    ];
+    grades.sort();
+    assert_eq!(
+        grades,
+        vec![
+            Grade::Kyouiku(1),
+            Grade::Kyouiku(3),
+            Grade::Kyouiku(6),
+            Grade::Jouyou,
+            Grade::Jinmeiyou,
+            Grade::JouyouVariant,
+        ]
+    );
+}
+
+#[cfg(feature = "sample-data")]
+#[test]
+fn kanjidic_bundled_sample() {
+    let dict = Kanjidic::sample();
+    assert_eq!(dict.entries.len(), 20);
+    assert!(dict.validate().is_empty());
+    assert!(
+        dict.entries
+            .iter()
+            .all(|e| e.grade == Some(Grade::Kyouiku(1))
+                && e.ucs_value() == e.as_char().map(u32::from))
+    );
+    assert_eq!(literals(&dict.search_reading("みず")), vec!["水"]);
+}
+
+#[test]
+fn kanjidic_entry_radicals() {
+    let dict = kanjidic_sample();
+
+    let a = dict.find_literal("亜").unwrap();
+    let classical = a.classical_radical().unwrap();
+    assert_eq!((classical.number, classical.value.as_str()), (7, "二"));
+    let nelson = a.nelson_radical().unwrap();
+    assert_eq!((nelson.number, nelson.value.as_str()), (1, "一"));
+    assert_eq!(nelson.classification, RadicalType::NelsonC);
+
+    let water = dict.find_literal("水").unwrap();
+    assert!(water.radical(RadicalType::NelsonC).is_none());
+    assert_eq!(water.nelson_radical().unwrap().number, 85);
+}
+
+#[test]
+fn kanjidic_to_xml_round_trip() {
+    let dict = kanjidic_sample();
+    assert_eq!(dict.to_xml().parse::<Kanjidic>().unwrap(), dict);
+
+    let dict = kanjidic_with(
+        r#"<character>
+        <literal>&amp;</literal>
+        <codepoint><cp_value cp_type="ucs">26</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">1</rad_value></radical>
+        <misc><stroke_count>1</stroke_count></misc>
+        <reading_meaning><rmgroup>
+        <reading r_type="ja_on" on_type="kan'you">アンド</reading>
+        <meaning>"and" &lt;&amp;&gt; 'ampersand'</meaning>
+        <meaning m_lang="fr">et</meaning>
+        </rmgroup></reading_meaning>
+        </character>"#,
+    );
+    let xml = dict.to_xml();
+    assert!(xml.contains("&quot;and&quot; &lt;&amp;&gt; &apos;ampersand&apos;"));
+    let parsed: Kanjidic = xml.parse().unwrap();
+    assert_eq!(parsed, dict);
+    assert_eq!(parsed.entries[0].literal, "&");
+}
+
+#[test]
+fn kanjidic_kanji_only() {
+    let character = |literal: &str| {
+        format!(
+            "<character><literal>{}</literal><codepoint/><radical/>\
+             <misc><stroke_count>1</stroke_count></misc></character>",
+            literal
+        )
+    };
+    let dict = kanjidic_with(
+        &[
+            character("亜"),
+            character("〆"),
+            character("\u{F900}"),
+            character("㐂"),
+            character("𠀋"),
+        ]
+        .concat(),
+    );
+
+    assert_eq!(literals(&dict.kanji_only()), vec!["亜", "㐂", "𠀋"]);
+    assert!(!dict.find_literal("〆").unwrap().is_kanji());
+}
+
+#[test]
+fn kanjidic_reading_index() {
+    let dict = kanjidic_sample();
+    let index = dict.build_reading_index();
+    for query in &["あ", "ア", "つぐ", "つ.ぐ", "みず", "スイ", "や", "none"] {
+        let indexed = index.search(query);
+        assert_eq!(literals(&indexed), literals(&dict.search_reading(query)));
+    }
+    assert!(!index.search("みず").is_empty());
+}
+
+#[test]
+fn kanjidic_dtd_dic_ref_types() {
+    // Every dr_type in the kanjidic2 DTD.
+    let codes = [
+        "nelson_c",
+        "nelson_n",
+        "halpern_njecd",
+        "halpern_kkd",
+        "halpern_kkld",
+        "halpern_kkld_2ed",
+        "heisig",
+        "heisig6",
+        "gakken",
+        "oneill_names",
+        "oneill_kk",
+        "moro",
+        "henshall",
+        "sh_kk",
+        "sh_kk2",
+        "sakade",
+        "jf_cards",
+        "henshall3",
+        "tutt_cards",
+        "crowley",
+        "kanji_in_context",
+        "busy_people",
+        "kodansha_compact",
+        "maniette",
+    ];
+    let dic_refs: String = codes
+        .iter()
+        .map(|code| format!(r#"<dic_ref dr_type="{}">1</dic_ref>"#, code))
+        .collect();
+    let dict = kanjidic_with(&format!(
+        "<character><literal>亜</literal><codepoint/><radical/>\
+         <misc><stroke_count>7</stroke_count></misc>\
+         <dic_number>{}</dic_number></character>",
+        dic_refs
+    ));
+
+    let kinds: Vec<_> = dict.entries[0]
+        .dic_refs
+        .iter()
+        .map(|d| d.kind().unwrap())
+        .collect();
+    assert_eq!(kinds.len(), codes.len());
+    assert_eq!(kinds.iter().collect::<HashSet<_>>().len(), codes.len());
+    for (kind, code) in kinds.iter().zip(codes.iter()) {
+        assert_eq!(kind.as_str(), *code);
+    }
+    assert_eq!(DicRefKind::ALL.len(), codes.len());
+}
+
+#[test]
+fn kanjidic_value_ranges() {
+    let dict = kanjidic_sample();
+    let strokes = dict.stroke_count_range();
+    assert_eq!(*strokes.start(), 1);
+    assert!(dict
+        .entries
+        .iter()
+        .all(|e| strokes.contains(&e.stroke_count)));
+
+    let freqs = dict.freq_range().unwrap();
+    assert!(dict
+        .entries
+        .iter()
+        .filter_map(|e| e.freq)
+        .all(|f| freqs.contains(&f)));
+
+    let empty = kanjidic_with("");
+    assert_eq!(empty.stroke_count_range(), 0..=0);
+    assert_eq!(empty.freq_range(), None);
+}
+
+#[test]
+fn kanjidic_unknown_tags() {
+    let xml = kanjidic_xml(
+        "<character><literal>亜</literal><codepoint/><radical/>\
+         <misc><stroke_count>7</stroke_count><new_misc>1</new_misc></misc>\
+         <query_code><q_code qc_type=\"skip\">4-7-1</q_code></query_code>\
+         <extra/><extra/></character>",
+    );
+    let options = ParseOptions {
+        report_unknown_tags: true,
+        ..ParseOptions::default()
+    };
+    let (dict, warnings) = Kanjidic::from_str_with_options(&xml, options).unwrap();
+    assert_eq!(dict.entries.len(), 1);
+    let tags: Vec<_> = warnings
+        .iter()
+        .filter_map(|w| match w {
+            ParseWarning::UnknownTag(tag) => Some(tag.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(tags, vec!["new_misc", "extra"]);
+
+    let (_, warnings) = Kanjidic::from_str_with_options(&xml, ParseOptions::default()).unwrap();
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn kanjidic_unknown_group_children() {
+    let xml = kanjidic_xml(
+        "<character><literal>亜</literal>\
+         <codepoint><cp_value cp_type=\"ucs\">4e9c</cp_value><cp_extra>1</cp_extra></codepoint>\
+         <radical><rad_value rad_type=\"classical\">7</rad_value><rad_extra/></radical>\
+         <misc><stroke_count>7</stroke_count></misc>\
+         <dic_number><dic_ref dr_type=\"nelson_c\">43</dic_ref><dic_extra/></dic_number>\
+         </character>",
+    );
+    let options = ParseOptions {
+        report_unknown_tags: true,
+        ..ParseOptions::default()
+    };
+    let (dict, warnings) = Kanjidic::from_str_with_options(&xml, options).unwrap();
+    let kanji = &dict.entries[0];
+    assert_eq!(kanji.codepoints.len(), 1);
+    assert_eq!(kanji.radicals.len(), 1);
+    assert_eq!(kanji.dic_refs.len(), 1);
+    let tags: Vec<_> = warnings
+        .iter()
+        .filter_map(|w| match w {
+            ParseWarning::UnknownTag(tag) => Some(tag.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(tags, vec!["cp_extra", "rad_extra", "dic_extra"]);
+}
+
+// Compile-time check; this fails to build if a field stops being Send or Sync.
+#[test]
+fn kanjidic_is_send_sync() {
+    fn _assert_send_sync<T: Send + Sync>() {}
+    _assert_send_sync::<Kanjidic>();
+    _assert_send_sync::<Entry>();
+    _assert_send_sync::<ReadingIndex>();
+    _assert_send_sync::<LiteralIndex>();
+    _assert_send_sync::<ParseError>();
+}
+
+#[test]
+fn kanjidic_entry_counts() {
+    let dict = kanjidic_sample();
+    let a = dict.find_literal("亜").unwrap();
+    assert_eq!(a.reading_count(), 5);
+    assert_eq!(a.meaning_count(), 6);
+
+    let dict = kanjidic_with(
+        "<character><literal>亜</literal><codepoint/><radical/>\
+         <misc><stroke_count>7</stroke_count></misc>\
+         <reading_meaning>\
+         <rmgroup><reading r_type=\"ja_on\">ア</reading><meaning>Asia</meaning></rmgroup>\
+         <rmgroup><reading r_type=\"ja_kun\">つ.ぐ</reading>\
+         <reading r_type=\"pinyin\">ya4</reading></rmgroup>\
+         <nanori>や</nanori>\
+         </reading_meaning></character>",
+    );
+    let e = &dict.entries[0];
+    assert_eq!((e.reading_count(), e.meaning_count()), (3, 1));
+}
+
+#[test]
+fn kanjidic_kradfile_components() {
+    let kradfile: Radicals = "# KRADFILE\n\
+                              語 : 言 口 五\n\
+                              亜 : ｜ 一 口\n"
+        .parse()
+        .unwrap();
+    assert_eq!(kradfile.components('語').unwrap(), &['言', '口', '五']);
+    assert!("語 言 口".parse::<Radicals>().is_err());
+
+    let mut dict = kanjidic_with(
+        "<character><literal>語</literal><codepoint/><radical/>\
+         <misc><stroke_count>14</stroke_count></misc></character>\
+         <character><literal>一</literal><codepoint/><radical/>\
+         <misc><stroke_count>1</stroke_count></misc></character>",
+    );
+    assert!(dict.entries[0].components().is_empty());
+    dict.attach_kradfile(&kradfile);
+    assert_eq!(
+        dict.find_literal("語").unwrap().components(),
+        &['言', '口', '五']
+    );
+    assert!(dict.find_literal("一").unwrap().components().is_empty());
+}
+
+#[test]
+fn kanjidic_missing_fields() {
+    let dict = kanjidic_with(
+        "<character><literal>亜</literal><codepoint/><radical/>\
+         <misc><stroke_count>7</stroke_count></misc>\
+         <reading_meaning><rmgroup>\
+         <reading r_type=\"ja_on\">ア</reading>\
+         <meaning>Asia</meaning><meaning m_lang=\"fr\">Asie</meaning>\
+         </rmgroup></reading_meaning></character>\
+         <character><literal>丂</literal><codepoint/><radical/>\
+         <misc><stroke_count>2</stroke_count></misc>\
+         <reading_meaning><rmgroup>\
+         <reading r_type=\"ja_kun\">かんが.える</reading>\
+         </rmgroup></reading_meaning></character>",
+    );
+
+    assert_eq!(literals(&dict.missing_meaning("en")), vec!["丂"]);
+    assert_eq!(literals(&dict.missing_meaning("es")), vec!["亜", "丂"]);
+    let no_on = dict.missing_reading(|t| matches!(t, ReadingType::Onyomi(..)));
+    assert_eq!(literals(&no_on), vec!["丂"]);
+    assert!(dict
+        .missing_reading(|t| matches!(t, ReadingType::Onyomi(..) | ReadingType::Kunyomi(..)))
+        .is_empty());
+}
+
+#[test]
+fn kanjidic_find_meanings() {
+    let dict = kanjidic_sample();
+    let pairs = dict.find_meanings(|m| &*m.language == "en" && m.content.contains("next"));
+    let found: Vec<_> = pairs
+        .iter()
+        .map(|(e, m)| (e.literal.as_str(), m.content.as_str()))
+        .collect();
+    assert!(found.contains(&("亜", "rank next")));
+
+    let pairs = dict.find_meanings(|m| m.content.starts_with("come") || m.content == "Asia");
+    let a: Vec<_> = pairs
+        .iter()
+        .filter(|(e, _)| e.literal == "亜")
+        .map(|(_, m)| m.content.as_str())
+        .collect();
+    assert_eq!(a, vec!["Asia", "come after"]);
+}
+
+#[test]
+fn kanjidic_normalize_languages() {
+    let xml = kanjidic_xml(
+        "<character><literal>亜</literal><codepoint/><radical/>\
+         <misc><stroke_count>7</stroke_count></misc>\
+         <reading_meaning><rmgroup>\
+         <meaning>Asia</meaning><meaning m_lang=\"EN\">rank next</meaning>\
+         <meaning m_lang=\"Fr\">Asie</meaning><meaning m_lang=\"pt_BR\">Ásia</meaning>\
+         <meaning m_lang=\"pt-br\">seguinte</meaning>\
+         </rmgroup></reading_meaning></character>",
+    );
+    let dict: Kanjidic = xml.parse().unwrap();
+    let langs: Vec<_> = dict.entries[0].meanings().map(|m| &*m.language).collect();
+    assert_eq!(langs, vec!["en", "en", "fr", "pt-br", "pt-br"]);
+
+    let options = ParseOptions {
+        normalize_languages: false,
+        ..ParseOptions::default()
+    };
+    let (dict, _) = Kanjidic::from_str_with_options(&xml, options).unwrap();
+    let langs: Vec<_> = dict.entries[0].meanings().map(|m| &*m.language).collect();
+    assert_eq!(langs, vec!["en", "EN", "Fr", "pt_BR", "pt-br"]);
+}
+
+#[test]
+fn kanjidic_unknown_radical_number() {
+    let dict = kanjidic_with(
+        "<character><literal>亜</literal><codepoint/>\
+         <radical><rad_value rad_type=\"classical\">7</rad_value>\
+         <rad_value rad_type=\"nelson_c\">215</rad_value></radical>\
+         <misc><stroke_count>7</stroke_count></misc></character>",
+    );
+    let a = &dict.entries[0];
+    assert_eq!(a.classical_radical().unwrap().value, "二");
+    let nelson = a.radical(RadicalType::NelsonC).unwrap();
+    assert_eq!((nelson.number, nelson.value.as_str()), (215, ""));
+}
+
+#[cfg(feature = "csv")]
+#[test]
+fn kanjidic_to_csv() {
+    use crate::kanjidic::Column;
+
+    let dict = kanjidic_sample();
+    let columns = [
+        Column::Literal,
+        Column::StrokeCount,
+        Column::Grade,
+        Column::Jlpt,
+        Column::FirstMeaning,
+        Column::Meanings,
+        Column::Kunyomi,
+    ];
+    let csv = dict.to_csv(&columns);
+
+    let mut reader = ::csv::Reader::from_reader(csv.as_bytes());
+    let header: Vec<_> = reader
+        .headers()
+        .unwrap()
+        .iter()
+        .map(str::to_owned)
+        .collect();
+    assert_eq!(
+        header,
+        vec!["literal", "strokes", "grade", "jlpt", "meaning", "meanings", "kun"]
+    );
+    let rows: Vec<_> = reader.records().map(Result::unwrap).collect();
+    assert_eq!(rows.len(), dict.entries.len());
+    let a = rows.iter().find(|r| &r[0] == "亜").unwrap();
+    assert_eq!(&a[1], "7");
+    assert_eq!(&a[4], "Asia");
+    assert_eq!(&a[5], "Asia; rank next; come after; -ous");
+    assert_eq!(&a[6], "つ.ぐ");
+}
+
+#[test]
+fn kanjidic_entries_with_positions() {
+    let entries = Kanjidic::entries_with_positions(KANJIDIC_SAMPLE).unwrap();
+    assert_eq!(entries.len(), kanjidic_sample().entries.len());
+    for (entry, range) in &entries {
+        let source = &KANJIDIC_SAMPLE[range.clone()];
+        assert!(source.starts_with("<character>"));
+        assert!(source.ends_with("</character>"));
+        assert!(source.contains(&format!("<literal>{}</literal>", entry.literal)));
+    }
+}
+
+#[test]
+fn kanjidic_reading_matches_kana() {
+    let kun = |value: &str| Reading {
+        value: value.to_owned(),
+        typ: ReadingType::Kunyomi(None),
+    };
+    assert!(kun("た.べる").matches_kana("たべる"));
+    assert!(kun("た.べる").matches_kana("タベル"));
+    assert!(!kun("た.べる").matches_kana("た"));
+    assert!(kun("-ほど").matches_kana("ほど"));
+    assert!(kun("ひと-").matches_kana("ひと"));
+    assert!(kun("みず").matches_kana("みず"));
+    assert!(!kun("みず").matches_kana("みずうみ"));
+
+    let on = Reading {
+        value: "スイ".to_owned(),
+        typ: ReadingType::Onyomi(None, OnyomiType::None),
+    };
+    assert!(on.matches_kana("すい"));
+    assert!(on.matches_kana("スイ"));
+}
+
+#[test]
+fn kanjidic_grade_labels() {
+    assert_eq!(Grade::Kyouiku(3).label(), "Grade 3 (Kyōiku)");
+    assert_eq!(Grade::Jouyou.label(), "Jōyō");
+    assert_eq!(Grade::Jinmeiyou.label(), "Jinmeiyō");
+    assert_eq!(Grade::JouyouVariant.label(), "Jinmeiyō (Jōyō variant)");
+
+    let dict = kanjidic_sample();
+    assert_eq!(
+        dict.find_literal("亜").unwrap().grade_label().as_deref(),
+        Some("Jōyō")
+    );
+    assert_eq!(dict.find_literal("丂").unwrap().grade_label(), None);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn kanjidic_write_jsonl() {
+    let dict = kanjidic_sample();
+    let mut buf = Vec::new();
+    dict.write_jsonl(&mut buf).unwrap();
+
+    let jsonl = String::from_utf8(buf).unwrap();
+    let lines: Vec<_> = jsonl.lines().collect();
+    assert_eq!(lines.len(), dict.entries.len());
+    for (line, e) in lines.iter().zip(&dict.entries) {
+        let value: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(value, e.to_value());
+    }
+}
+
+#[test]
+fn kanjidic_find_by_jis() {
+    let dict = kanjidic_sample();
+    assert_eq!(
+        dict.find_by_jis("jis208", 1, "16-01").unwrap().literal,
+        "亜"
+    );
+    assert_eq!(dict.find_by_jis("jis208", 1, "16-1").unwrap().literal, "亜");
+    assert_eq!(dict.find_by_jis("jis208", 1, "1601").unwrap().literal, "亜");
+    assert!(dict.find_by_jis("jis208", 2, "16-01").is_none());
+    assert!(dict.find_by_jis("jis208", 1, "16").is_none());
+
+    let dict = kanjidic_with(
+        "<character><literal>𠀋</literal>\
+         <codepoint><cp_value cp_type=\"jis213\">2-1-3</cp_value></codepoint>\
+         <radical/><misc><stroke_count>4</stroke_count></misc></character>",
+    );
+    assert_eq!(
+        dict.find_by_jis("jis213", 2, "01-03").unwrap().literal,
+        "𠀋"
+    );
+    assert!(dict.find_by_jis("jis208", 2, "01-03").is_none());
+
+    // 1-16-01 is 亜 in JIS X 0208 and 丂 in JIS X 0212.
+    let dict = kanjidic_with(
+        "<character><literal>亜</literal>\
+         <codepoint><cp_value cp_type=\"jis208\">1-16-01</cp_value></codepoint>\
+         <radical/><misc><stroke_count>7</stroke_count></misc></character>\
+         <character><literal>丂</literal>\
+         <codepoint><cp_value cp_type=\"jis212\">1-16-01</cp_value></codepoint>\
+         <radical/><misc><stroke_count>2</stroke_count></misc></character>",
+    );
+    assert_eq!(
+        dict.find_by_jis("jis208", 1, "16-01").unwrap().literal,
+        "亜"
+    );
+    assert_eq!(
+        dict.find_by_jis("jis212", 1, "16-01").unwrap().literal,
+        "丂"
+    );
+    assert!(dict.find_by_jis("jis213", 1, "16-01").is_none());
+}
+
+#[test]
+fn kanjidic_lenient_unknown_onyomi_type() {
+    let contents = kanjidic_xml(
+        r#"<character>
+        <literal>水</literal>
+        <codepoint><cp_value cp_type="ucs">6c34</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">85</rad_value></radical>
+        <misc><stroke_count>4</stroke_count></misc>
+        <reading_meaning><rmgroup>
+        <reading r_type="ja_on" on_type="wei">スイ</reading>
+        </rmgroup></reading_meaning>
+        </character>"#,
+    );
+    assert!(matches!(
+        contents.parse::<Kanjidic>(),
+        Err(ParseError::ParseEnum(_))
+    ));
+
+    let (dict, warnings) = Kanjidic::from_str_lenient(&contents).unwrap();
+    assert_eq!(
+        dict.entries[0].readings().next().unwrap().typ,
+        ReadingType::Onyomi(None, OnyomiType::Other("wei".to_owned()))
+    );
+    assert_eq!(warnings.len(), 1);
+    assert!(dict.to_xml().contains(r#"on_type="wei""#));
+}
+
+#[test]
+fn kanjidic_difficulty_score() {
+    let dict = kanjidic_with(
+        "<character><literal>一</literal><codepoint/><radical/>\
+         <misc><grade>1</grade><stroke_count>1</stroke_count><freq>2</freq></misc>\
+         </character>\
+         <character><literal>鬱</literal><codepoint/><radical/>\
+         <misc><grade>8</grade><stroke_count>29</stroke_count></misc>\
+         </character>\
+         <character><literal>龠</literal><codepoint/><radical/>\
+         <misc><stroke_count>17</stroke_count></misc></character>",
+    );
+    let one = dict.find_literal("一").unwrap();
+    let rare = dict.find_literal("鬱").unwrap();
+    assert!(one.difficulty_score() < 5.0);
+    assert!(rare.difficulty_score() > one.difficulty_score());
+    let ungraded = dict.find_literal("龠").unwrap();
+    assert!((0.0..=100.0).contains(&ungraded.difficulty_score()));
+
+    let strokes_only = DifficultyWeights {
+        strokes: 1.0,
+        grade: 0.0,
+        frequency: 0.0,
+    };
+    assert!((one.difficulty_score_with(&strokes_only) - 100.0 / 30.0).abs() < 1e-4);
+    let none = DifficultyWeights {
+        strokes: 0.0,
+        ..strokes_only
+    };
+    assert_eq!(rare.difficulty_score_with(&none), 0.0);
+}
+
+#[test]
+fn kanjidic_index_of() {
+    let dict = kanjidic_sample();
+    for literal in &["亜", "水", "丂"] {
+        let index = dict.index_of(literal).unwrap();
+        assert_eq!(dict.entry(index), dict.find_literal(literal));
+    }
+    assert_eq!(dict.index_of("犬"), None);
+    assert_eq!(dict.entry(dict.entries.len()), None);
+}
+
+#[test]
+fn kanjidic_suspicious_meanings() {
+    let mut dict = kanjidic_with(
+        "<character><literal>亜</literal><codepoint/><radical/>\
+         <misc><stroke_count>7</stroke_count></misc>\
+         <reading_meaning><rmgroup>\
+         <meaning>Asia</meaning><meaning>rank next</meaning>\
+         <meaning m_lang=\"fr\">Asie</meaning><meaning m_lang=\"fr\">suiv\u{FFFD}nt</meaning>\
+         <meaning m_lang=\"es\">Asiático</meaning>\
+         </rmgroup></reading_meaning></character>",
+    );
+    // XML doesn't allow most control characters even as references.
+    dict.entries[0].reading_meanings[0].meanings[1].content = "rank\u{7}next".to_owned();
+    let suspicious: Vec<_> = dict
+        .suspicious_meanings()
+        .into_iter()
+        .map(|(_, m)| m.content.as_str())
+        .collect();
+    assert_eq!(suspicious, vec!["rank\u{7}next", "suiv\u{FFFD}nt"]);
+
+    let printable: Vec<_> = dict.entries[0]
+        .meanings()
+        .map(Meaning::is_ascii_printable)
+        .collect();
+    assert_eq!(printable, vec![true, false, true, false, false]);
+}
+
+#[test]
+fn kanjidic_interned_languages() {
+    use std::sync::Arc;
+
+    let dict = kanjidic_sample();
+    let a = dict.find_literal("亜").unwrap();
+    let water = dict.find_literal("水").unwrap();
+    let english: Vec<_> = a
+        .meanings()
+        .chain(water.meanings())
+        .filter(|m| &*m.language == "en")
+        .collect();
+    assert!(english.len() > 1);
+    assert!(english
+        .iter()
+        .all(|m| Arc::ptr_eq(&m.language, &english[0].language)));
+
+    let french = a.meanings().find(|m| &*m.language == "fr").unwrap();
+    assert!(!Arc::ptr_eq(&french.language, &english[0].language));
+}
+
+#[test]
+fn kanjidic_validate_dtd() {
+    let xml = kanjidic_xml(
+        "<character><literal>亜</literal><codepoint/>\
+         <misc><stroke_count>7</stroke_count></misc></character>\
+         <character><codepoint/><radical/></character>\
+         <character><literal>一</literal><codepoint/><radical/>\
+         <misc><stroke_count>1</stroke_count></misc></character>",
+    );
+    assert!(matches!(
+        xml.parse::<Kanjidic>(),
+        Err(ParseError::Xml(XmlError::MissingTag { .. }))
+    ));
+
+    let options = ParseOptions {
+        validate_dtd: true,
+        ..ParseOptions::default()
+    };
+    let (dict, warnings) = Kanjidic::from_str_with_options(&xml, options).unwrap();
+    assert_eq!(
+        literals(&dict.entries.iter().collect::<Vec<_>>()),
+        vec!["一"]
+    );
+    let missing: Vec<_> = warnings
+        .iter()
+        .map(|w| match w {
+            ParseWarning::MissingElement { literal, tag } => (literal.as_deref(), tag.as_str()),
+            _ => panic!("unexpected warning: {}", w),
+        })
+        .collect();
+    assert_eq!(
+        missing,
+        vec![(Some("亜"), "radical"), (None, "literal"), (None, "misc")]
+    );
+    assert_eq!(warnings[0].to_string(), "亜: required tag missing: radical");
+}
+
+#[test]
+fn kanjidic_entry_codepoint() {
+    let dict = kanjidic_sample();
+    let a = dict.find_literal("亜").unwrap();
+    assert_eq!(a.codepoint("ucs"), Some("4e9c"));
+    assert_eq!(a.codepoint("jis208"), Some("1-16-01"));
+    assert_eq!(a.codepoint("jis212"), None);
+    assert_eq!(a.ucs(), Some("4e9c"));
+    assert_eq!(a.ucs_value(), Some(0x4e9c));
+}
+
+#[test]
+fn kanjidic_entry_merge_from() {
+    let dict = kanjidic_with(
+        r#"<character>
+        <literal>学</literal>
+        <codepoint><cp_value cp_type="ucs">5b66</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">39</rad_value></radical>
+        <misc><grade>1</grade><stroke_count>8</stroke_count></misc>
+        <dic_number><dic_ref dr_type="heisig">322</dic_ref></dic_number>
+        <reading_meaning><rmgroup>
+        <reading r_type="ja_on">ガク</reading>
+        <meaning>study</meaning>
+        </rmgroup></reading_meaning>
+        </character>
+        <character>
+        <literal>学</literal>
+        <codepoint><cp_value cp_type="ucs">5b66</cp_value><cp_value cp_type="jis208">1-19-56</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">39</rad_value></radical>
+        <misc><stroke_count>8</stroke_count><freq>63</freq><jlpt>4</jlpt></misc>
+        <dic_number><dic_ref dr_type="heisig">322</dic_ref><dic_ref dr_type="nelson_c">1281</dic_ref></dic_number>
+        <reading_meaning><rmgroup>
+        <reading r_type="ja_on">ガク</reading>
+        <reading r_type="ja_kun">まな.ぶ</reading>
+        <meaning>study</meaning>
+        <meaning>learning</meaning>
+        </rmgroup>
+        <nanori>さね</nanori>
+        </reading_meaning>
+        </character>"#,
+    );
+    let mut merged = dict.entries[0].clone();
+    merged.merge_from(&dict.entries[1]);
+
+    assert_eq!(merged.grade, Some(Grade::Kyouiku(1)));
+    assert_eq!(
+        (merged.stroke_count, merged.freq, merged.old_jlpt),
+        (8, Some(63), Some(4))
+    );
+    assert_eq!(merged.codepoint("jis208"), Some("1-19-56"));
+    assert_eq!(merged.codepoints.len(), 2);
+    assert_eq!(merged.dic_refs.len(), 2);
+    let readings: Vec<_> = merged.readings().map(|r| r.value.as_str()).collect();
+    assert_eq!(readings, vec!["ガク", "まな.ぶ"]);
+    let meanings: Vec<_> = merged.meanings().map(|m| m.content.as_str()).collect();
+    assert_eq!(meanings, vec!["study", "learning"]);
+    assert_eq!(merged.nanori_readings, vec!["さね"]);
+
+    let mut again = merged.clone();
+    again.merge_from(&dict.entries[1]);
+    assert_eq!(again, merged);
+}
+
+#[test]
+fn kanjidic_reading_to_romaji() {
+    let kun = |value: &str| Reading {
+        value: value.to_owned(),
+        typ: ReadingType::Kunyomi(None),
+    };
+    let romaji = |value: &str| {
+        let r = kun(value);
+        [
+            RomajiSystem::Hepburn,
+            RomajiSystem::Kunrei,
+            RomajiSystem::NihonShiki,
+        ]
+        .iter()
+        .map(|&system| r.to_romaji_with(system).unwrap())
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(kun("た.べる").to_romaji().unwrap(), "taberu");
+    assert_eq!(romaji("-ほど"), vec!["hodo", "hodo", "hodo"]);
+    assert_eq!(romaji("し"), vec!["shi", "si", "si"]);
+    assert_eq!(romaji("ち"), vec!["chi", "ti", "ti"]);
+    assert_eq!(romaji("つ"), vec!["tsu", "tu", "tu"]);
+    assert_eq!(romaji("ふ"), vec!["fu", "hu", "hu"]);
+    assert_eq!(romaji("じ"), vec!["ji", "zi", "zi"]);
+    assert_eq!(romaji("ぢ"), vec!["ji", "zi", "di"]);
+    assert_eq!(romaji("づ"), vec!["zu", "zu", "du"]);
+    assert_eq!(romaji("を"), vec!["o", "o", "wo"]);
+    assert_eq!(romaji("しゃ"), vec!["sha", "sya", "sya"]);
+    assert_eq!(romaji("ちょう"), vec!["chou", "tyou", "tyou"]);
+    assert_eq!(romaji("じゅ"), vec!["ju", "zyu", "zyu"]);
+    assert_eq!(romaji("ぢゃ"), vec!["ja", "zya", "dya"]);
+    assert_eq!(romaji("きょ"), vec!["kyo", "kyo", "kyo"]);
+    assert_eq!(romaji("まっち"), vec!["matchi", "matti", "matti"]);
+    assert_eq!(romaji("がっこう"), vec!["gakkou", "gakkou", "gakkou"]);
+    assert_eq!(romaji("しんや"), vec!["shin'ya", "sin'ya", "sin'ya"]);
+
+    let on = Reading {
+        value: "スイ".to_owned(),
+        typ: ReadingType::Onyomi(None, OnyomiType::None),
+    };
+    assert_eq!(on.to_romaji().unwrap(), "sui");
+    let pinyin = Reading {
+        value: "shui3".to_owned(),
+        typ: ReadingType::Pinyin,
+    };
+    assert_eq!(pinyin.to_romaji(), None);
+}
+
+#[test]
+fn kanjidic_onyomi_by_type() {
+    let dict = kanjidic_with(
+        r#"<character>
+        <literal>行</literal>
+        <codepoint/><radical/>
+        <misc><stroke_count>6</stroke_count></misc>
+        <reading_meaning><rmgroup>
+        <reading r_type="ja_on" on_type="kan">コウ</reading>
+        <reading r_type="ja_on" on_type="go">ギョウ</reading>
+        <reading r_type="ja_on" on_type="tou">アン</reading>
+        <reading r_type="ja_on" on_type="kan">カウ</reading>
+        <reading r_type="ja_on">ゴウ</reading>
+        <reading r_type="ja_kun">い.く</reading>
+        </rmgroup></reading_meaning>
+        </character>"#,
+    );
+    let groups = dict.entries[0].onyomi_by_type();
+    assert_eq!(groups.len(), 4);
+    assert_eq!(groups[&OnyomiType::Kan], vec!["コウ", "カウ"]);
+    assert_eq!(groups[&OnyomiType::Go], vec!["ギョウ"]);
+    assert_eq!(groups[&OnyomiType::Tou], vec!["アン"]);
+    assert_eq!(groups[&OnyomiType::None], vec!["ゴウ"]);
+}
+
+#[test]
+fn kanjidic_from_document() {
+    let dict = {
+        let doc = roxmltree::Document::parse(KANJIDIC_SAMPLE).unwrap();
+        let characters = doc
+            .descendants()
+            .filter(|n| n.has_tag_name("character"))
+            .count();
+        let dict = Kanjidic::from_document(&doc).unwrap();
+        assert_eq!(dict.entries.len(), characters);
+        dict
+    };
+    assert_eq!(dict, kanjidic_sample());
+}
+
+#[test]
+fn kanjidic_kanji_with_reading() {
+    let character = |literal: &str, freq: Option<u32>, readings: &str| {
+        format!(
+            "<character><literal>{}</literal><codepoint/><radical/>\
+             <misc><stroke_count>1</stroke_count>{}</misc>\
+             <reading_meaning><rmgroup>{}</rmgroup></reading_meaning></character>",
+            literal,
+            freq.map(|f| format!("<freq>{}</freq>", f))
+                .unwrap_or_default(),
+            readings
+        )
+    };
+    let on = |r: &str| format!("<reading r_type=\"ja_on\">{}</reading>", r);
+    let kun = |r: &str| format!("<reading r_type=\"ja_kun\">{}</reading>", r);
+    let dict = kanjidic_with(
+        &[
+            character("校", Some(294), &on("コウ")),
+            character("高", Some(64), &[on("コウ"), kun("たか.い")].concat()),
+            character("孝", None, &on("コウ")),
+            character(
+                "行",
+                Some(20),
+                &[on("コウ"), on("ギョウ"), kun("い.く")].concat(),
+            ),
+            character("好", Some(423), &[on("コウ"), kun("この.む")].concat()),
+            character("水", Some(223), &[on("スイ"), kun("みず")].concat()),
+        ]
+        .concat(),
+    );
+
+    let kou = dict.kanji_with_onyomi("こう");
+    assert_eq!(literals(&kou), vec!["行", "高", "校", "好", "孝"]);
+    assert_eq!(literals(&dict.kanji_with_onyomi("コウ")), literals(&kou));
+    assert_eq!(literals(&dict.kanji_with_kunyomi("たかい")), vec!["高"]);
+    assert!(dict.kanji_with_kunyomi("コウ").is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn kanjidic_serde_schema() {
+    let dict = kanjidic_sample();
+    let a = dict.find_literal("亜").unwrap();
+    let value = serde_json::to_value(a).unwrap();
+
+    let keys: Vec<_> = value
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    assert_eq!(
+        keys,
+        vec![
+            "codepoints",
+            "dicRefs",
+            "freq",
+            "grade",
+            "jlpt",
+            "literal",
+            "nanori",
+            "radNames",
+            "radicals",
+            "readingMeaning",
+            "strokeCount",
+            "strokeMiscounts",
+            "variants",
+        ]
+    );
+    assert_eq!(value["grade"], "jouyou");
+    assert_eq!(value["radicals"][1]["classification"], "nelson_c");
+    let reading = &value["readingMeaning"][0]["readings"][3];
+    assert_eq!(reading["type"]["ja_on"], serde_json::json!([null, "none"]));
+    assert!(value["dicRefs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|d| d["moro"]["volume"] == 1));
+
+    let ungraded = serde_json::to_value(dict.find_literal("丂").unwrap()).unwrap();
+    assert!(ungraded.get("grade").is_none());
+    assert!(ungraded.get("jlpt").is_none());
+
+    let json = serde_json::to_string(&dict).unwrap();
+    assert!(json.contains(r#""fileVersion":"#));
+    assert_eq!(serde_json::from_str::<Kanjidic>(&json).unwrap(), dict);
+}
+
+#[test]
+fn kanjidic_find_literal_owned() {
+    let mut dict = kanjidic_sample();
+    let mut owned = dict.find_literal_owned("亜").unwrap();
+    assert_eq!(Some(&owned), dict.find_literal("亜"));
+
+    owned.stroke_count = 99;
+    assert_eq!(dict.find_literal("亜").unwrap().stroke_count, 7);
+    dict.entries.clear();
+    assert_eq!(owned.literal, "亜");
+    assert!(dict.find_literal_owned("亜").is_none());
+}
+
+#[test]
+fn kanjidic_entry_fields() {
+    let dict = kanjidic_sample();
+    let fields = dict.find_literal("亜").unwrap().fields();
+    let value = |key: &str| {
+        fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    };
+    assert_eq!(value("Stroke count"), Some("7"));
+    assert_eq!(value("Grade"), Some("Jōyō"));
+    assert_eq!(value("JLPT"), Some("1"));
+    assert_eq!(value("On"), Some("ア"));
+    for key in &["Frequency", "Kun", "Nanori", "Meanings"] {
+        assert!(value(key).is_some(), "{}", key);
+    }
+
+    // 丂 has no grade or meanings, so those keys are left out.
+    let fields = dict.find_literal("丂").unwrap().fields();
+    assert!(fields.iter().all(|(k, _)| k != "Grade" && k != "Meanings"));
+}
+
+#[test]
+fn kanjidic_radical_number() {
+    let dict = kanjidic_sample();
+    let kanji = dict.find_literal("亜").unwrap();
+    assert_eq!(kanji.radical_number(RadicalType::Classical), Some(7));
+    assert_eq!(kanji.radical_number(RadicalType::NelsonC), Some(1));
+    assert!(kanji.classical_radical().unwrap().is_classical());
+    assert!(kanji.radical(RadicalType::NelsonC).unwrap().is_nelson());
+    assert!(!kanji.classical_radical().unwrap().is_nelson());
+
+    // Unlike nelson_radical, there's no fallback to the classical radical.
+    let kanji = dict.find_literal("水").unwrap();
+    assert_eq!(kanji.radical_number(RadicalType::NelsonC), None);
+    assert_eq!(kanji.nelson_radical().unwrap().number, 85);
+}
+
+#[test]
+fn kanjidic_blank_meanings_skipped() {
+    let dict = kanjidic_with(
+        "<character>\
+         <literal>亜</literal>\
+         <codepoint><cp_value cp_type=\"ucs\">4e9c</cp_value></codepoint>\
+         <radical><rad_value rad_type=\"classical\">7</rad_value></radical>\
+         <misc><stroke_count>7</stroke_count></misc>\
+         <reading_meaning>\
+         <rmgroup>\
+         <meaning/>\
+         <meaning>Asia</meaning>\
+         <meaning m_lang=\"fr\">  </meaning>\
+         </rmgroup>\
+         <nanori></nanori>\
+         <nanori>や</nanori>\
+         </reading_meaning>\
+         </character>",
+    );
+    let kanji = &dict.entries[0];
+    let meanings: Vec<_> = kanji.meanings().map(|m| m.content.as_str()).collect();
+    assert_eq!(meanings, vec!["Asia"]);
+    assert_eq!(kanji.nanori_readings, vec!["や"]);
+}
+
+#[test]
+fn kanjidic_entries_mut() {
+    let mut dict = kanjidic_sample();
+    let index = dict.index_of("亜").unwrap();
+    dict.entries_mut()[index].stroke_count = 8;
+    assert_eq!(dict.find_literal("亜").unwrap().stroke_count, 8);
+
+    for e in dict.iter_mut() {
+        e.nanori_readings.clear();
+    }
+    assert!(dict.entries.iter().all(|e| e.nanori_readings.is_empty()));
+}
+
+#[test]
+fn kanjidic_study_order() {
+    let mut dict = kanjidic_sample();
+    let order = dict.study_order();
+    assert_eq!(literals(&order[..3]), vec!["一", "水", "亜"]);
+    assert_eq!(literals(&order[3..]), vec!["丂"]);
+
+    // Unranked kanji follow by stroke count rather than document order.
+    for e in dict.iter_mut().filter(|e| e.literal != "一") {
+        e.freq = None;
+    }
+    let order = dict.study_order();
+    assert_eq!(literals(&order), vec!["一", "丂", "水", "亜"]);
+}
+
+#[test]
+fn kanjidic_similar_to() {
+    let character = |literal: &str, radical: u32, strokes: u32| {
+        format!(
+            "<character>\
+             <literal>{}</literal>\
+             <codepoint><cp_value cp_type=\"ucs\">0</cp_value></codepoint>\
+             <radical><rad_value rad_type=\"classical\">{}</rad_value></radical>\
+             <misc><stroke_count>{}</stroke_count></misc>\
+             </character>",
+            literal, radical, strokes
+        )
+    };
+    let characters: String = [
+        ("水", 85, 4),
+        ("海", 85, 9),
+        ("氷", 85, 5),
+        ("池", 85, 6),
+        ("永", 85, 5),
+        ("木", 75, 4),
+    ]
+    .iter()
+    .map(|&(l, r, s)| character(l, r, s))
+    .collect();
+    let dict = kanjidic_with(&characters);
+
+    let similar = dict.similar_to("氷", 3);
+    assert_eq!(literals(&similar), vec!["永", "水", "池"]);
+    assert!(similar
+        .iter()
+        .all(|e| e.radical_number(RadicalType::Classical) == Some(85)));
+
+    assert_eq!(
+        literals(&dict.similar_to("水", 10)),
+        vec!["氷", "永", "池", "海"]
+    );
+    assert!(dict.similar_to("木", 3).is_empty());
+    assert!(dict.similar_to("火", 3).is_empty());
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn kanjidic_tracing_events() {
+    use std::fmt::Debug;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    // Records the message of each event and the name of each span.
+    #[derive(Default)]
+    struct Collector {
+        messages: Arc<Mutex<Vec<String>>>,
+        spans: Arc<Mutex<Vec<&'static str>>>,
+        next_id: AtomicU64,
+    }
+
+    struct Message<'a>(&'a mut String);
+
+    impl Visit for Message<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            if field.name() == "message" {
+                *self.0 = format!("{:?}", value);
+            }
+        }
+    }
+
+    impl Subscriber for Collector {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes) -> Id {
+            self.spans.lock().unwrap().push(span.metadata().name());
+            Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+
+        fn record(&self, _: &Id, _: &Record) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event) {
+            let mut message = String::new();
+            event.record(&mut Message(&mut message));
+            self.messages.lock().unwrap().push(message);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    let contents = kanjidic_xml(
+        r#"<character>
+        <literal>水</literal>
+        <codepoint><cp_value cp_type="ucs">6c34</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">85</rad_value></radical>
+        <misc><stroke_count>4</stroke_count><new_misc/></misc>
+        <reading_meaning><rmgroup>
+        <reading r_type="cantonese">seoi2</reading>
+        </rmgroup></reading_meaning>
+        </character>"#,
+    );
+    let collector = Collector::default();
+    let messages = collector.messages.clone();
+    let spans = collector.spans.clone();
+    tracing::subscriber::with_default(collector, || {
+        Kanjidic::from_str_lenient(&contents).unwrap();
+    });
+
+    let messages = messages.lock().unwrap();
+    assert_eq!(messages.len(), 4, "{:?}", messages);
+    assert_eq!(messages[0], "parsed header: database version 2020-153");
+    assert_eq!(messages[1], "skipped unknown tag: new_misc");
+    assert!(messages[2].starts_with("recovered from parse error: cantonese"));
+    assert_eq!(messages[3], "parsed 1 entries");
+    assert_eq!(*spans.lock().unwrap(), vec!["parse_kanjidic"]);
+}
+
+#[test]
+fn kanjidic_jouyou_readings() {
+    let dict = kanjidic_with(
+        r#"<character>
+        <literal>生</literal>
+        <codepoint><cp_value cp_type="ucs">751f</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">100</rad_value></radical>
+        <misc><stroke_count>5</stroke_count></misc>
+        <reading_meaning><rmgroup>
+        <reading r_type="ja_on" r_status="jy">セイ</reading>
+        <reading r_type="ja_on">サン</reading>
+        <reading r_type="ja_on" r_status="jy">ショウ</reading>
+        <reading r_type="ja_kun" r_status="jy">い.きる</reading>
+        <reading r_type="ja_kun">ふ</reading>
+        <reading r_type="pinyin" r_status="jy">sheng1</reading>
+        </rmgroup></reading_meaning>
+        </character>"#,
+    );
+    let kanji = &dict.entries[0];
+    assert_eq!(kanji.jouyou_onyomi(), vec!["セイ", "ショウ"]);
+    assert_eq!(kanji.jouyou_kunyomi(), vec!["い.きる"]);
+    assert_eq!(kanji.onyomi().len(), 3);
+    assert_eq!(kanji.kunyomi().len(), 2);
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn dictionaries_load_dir() {
+    use crate::dictionaries::Dictionaries;
+
+    // Unique per process, so that parallel runs don't share the directory.
+    let dir = env::temp_dir().join(format!("jmdict_load_dir_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("kanjidic2.xml"), KANJIDIC_SAMPLE).unwrap();
+    fs::write(
+        dir.join("JMdict_e"),
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!-- <kanjidic2> in a comment isn't the root -->\n\
+         <JMdict><entry><ent_seq>1000000</ent_seq>\
+         <r_ele><reb>ヽ</reb></r_ele>\
+         <sense><gloss>repetition mark</gloss></sense></entry></JMdict>",
+    )
+    .unwrap();
+    fs::write(dir.join("JMnedict.xml"), JMNEDICT_SAMPLE).unwrap();
+    fs::write(dir.join("notes.txt"), "<notes>").unwrap();
+    fs::write(dir.join("kanjidic2.xml.gz"), [0x1f, 0x8b, 0xff, 0xfe]).unwrap();
+    fs::create_dir_all(dir.join("old")).unwrap();
+
+    let dicts = Dictionaries::load_dir(&dir).unwrap();
+    assert_eq!(dicts.kanjidic.unwrap().entries.len(), 4);
+    assert!(dicts.jmdict.unwrap().find_seq(1000000).is_some());
+    assert_eq!(dicts.jmnedict.unwrap().entries.len(), 3);
+
+    fs::write(dir.join("kanjidic2_copy.xml"), KANJIDIC_SAMPLE).unwrap();
+    assert!(Dictionaries::load_dir(&dir).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(matches!(
+        Dictionaries::load_dir(&dir),
+        Err(ParseError::Io { path: Some(_), .. })
+    ));
+}
+
+#[test]
+fn kanjidic_subset() {
+    let characters: String = "日月火水木"
+        .chars()
+        .map(|c| {
+            format!(
+                "<character><literal>{}</literal>\
+                 <codepoint><cp_value cp_type=\"ucs\">{:x}</cp_value></codepoint>\
+                 <radical><rad_value rad_type=\"classical\">1</rad_value></radical>\
+                 <misc><stroke_count>4</stroke_count></misc></character>",
+                c, c as u32
+            )
+        })
+        .collect();
+    let dict = kanjidic_with(&format!(
+        "{}<character><literal>金</literal>\
+         <codepoint><cp_value cp_type=\"ucs\">91d1</cp_value></codepoint>\
+         <radical><rad_value rad_type=\"classical\">167</rad_value></radical>\
+         <misc><stroke_count>8</stroke_count></misc></character>",
+        characters
+    ));
+
+    let subset = dict.subset("木水火月日".chars());
+    assert_eq!(subset.len(), 5);
+    assert_eq!(literals(&subset), vec!["木", "水", "火", "月", "日"]);
+
+    let subset = dict.subset(vec!['金', 'あ', '日', '金']);
+    assert_eq!(literals(&subset), vec!["金", "日"]);
+}
+
+#[test]
+fn kanjidic_primary_readings() {
+    let dict = kanjidic_with(
+        r#"<character>
+        <literal>行</literal>
+        <codepoint><cp_value cp_type="ucs">884c</cp_value></codepoint>
+        <radical><rad_value rad_type="classical">144</rad_value></radical>
+        <misc><stroke_count>6</stroke_count></misc>
+        <reading_meaning><rmgroup>
+        <reading r_type="pinyin">xing2</reading>
+        <reading r_type="ja_kun">い.く</reading>
+        <reading r_type="ja_on">コウ</reading>
+        <reading r_type="ja_on">ギョウ</reading>
+        <reading r_type="ja_kun">ゆ.く</reading>
+        </rmgroup><rmgroup>
+        <reading r_type="ja_on">アン</reading>
+        <reading r_type="ja_on">コウ</reading>
+        <reading r_type="ja_kun">おこな.う</reading>
+        </rmgroup></reading_meaning>
+        </character>"#,
+    );
+    let kanji = &dict.entries[0];
+    assert_eq!(kanji.onyomi(), vec!["コウ", "ギョウ", "アン", "コウ"]);
+    assert_eq!(kanji.kunyomi(), vec!["い.く", "ゆ.く", "おこな.う"]);
+    assert_eq!(kanji.unique_onyomi(), vec!["コウ", "ギョウ", "アン"]);
+    assert_eq!(kanji.primary_onyomi(), Some("コウ"));
+    assert_eq!(kanji.primary_kunyomi(), Some("い.く"));
+
+    let dict = kanjidic_sample();
+    let kanji = dict.find_literal("丂").unwrap();
+    assert_eq!(kanji.primary_onyomi(), kanji.onyomi().first().copied());
+    assert_eq!(kanji.primary_kunyomi(), kanji.kunyomi().first().copied());
+}
+
+#[test]
+fn kanjidic_jlpt_counts() {
+    let dict = kanjidic_sample();
+    let counts = dict.jlpt_counts();
+    for (&level, &count) in &counts {
+        assert_eq!(dict.by_jlpt(level).len(), count);
+    }
+    let with_jlpt = dict.entries.iter().filter(|e| e.old_jlpt.is_some()).count();
+    assert_eq!(counts.values().sum::<usize>(), with_jlpt);
+    assert_eq!(counts.get(&4), Some(&2));
+    assert_eq!(counts, dict.stats().jlpt_distribution);
+}
+
+#[test]
+fn jmnedict_parse() {
+    let dict: JMnedict = JMNEDICT_SAMPLE.parse().unwrap();
+    assert_eq!(dict.entries.len(), 3);
+
+    let name = dict.find_seq(5000000).unwrap();
+    assert_eq!(name.kanji[0].text, "ゝ泉");
+    assert_eq!(name.reading[0].text, "こいずみ");
+    assert_eq!(name.trans[0].name_types, vec!["family or surname"]);
+    assert_eq!(
+        name.translations("eng").collect::<Vec<_>>(),
+        vec!["Koizumi"]
+    );
+
+    let japan = dict.search("にっぽん");
+    assert_eq!(japan.len(), 1);
+    let japan = japan[0];
+    assert_eq!(japan.trans.len(), 2);
+    assert_eq!(
+        japan.translations("eng").collect::<Vec<_>>(),
+        vec!["Japan", "Nihon"]
+    );
+    assert_eq!(japan.translations("ger").collect::<Vec<_>>(), vec!["Japan"]);
+    assert!(dict.search("東京").is_empty());
+}
+
+#[test]
+fn kanjidic_literal_index() {
+    let dict = kanjidic_sample();
+    let index = dict.build_literal_index();
+    for e in &dict.entries {
+        assert_eq!(
+            index.find_literal(&e.literal),
+            dict.find_literal(&e.literal)
+        );
+        assert_eq!(index.find_char(e.as_char().unwrap()), Some(e));
+    }
+    assert!(index.find_literal("火").is_none());
+    assert_eq!(
+        index.find_literals(&["水", "火"]),
+        dict.find_literals(&["水", "火"])
+    );
+    assert_eq!(
+        literals(&index.subset("水亜水".chars())),
+        literals(&dict.find_in_text("水亜水"))
+    );
+}
+
+// Uses only the in-memory parsers, which are available without the fs feature.
+#[test]
+fn parse_from_memory() {
+    let from_str: Kanjidic = KANJIDIC_SAMPLE.parse().unwrap();
+    let from_bytes = Kanjidic::from_bytes(KANJIDIC_SAMPLE.as_bytes()).unwrap();
+    assert_eq!(from_str.entries.len(), from_bytes.entries.len());
+    assert!(Kanjidic::from_bytes(&[0xff, 0xfe]).is_err());
+
+    let dict: JMDict = "<JMdict><entry><ent_seq>1000000</ent_seq>\
+                        <r_ele><reb>ヽ</reb></r_ele>\
+                        <sense><gloss>repetition mark</gloss></sense></entry></JMdict>"
+        .parse()
+        .unwrap();
+    assert_eq!(dict.find_seq(1000000).unwrap().reading[0].text, "ヽ");
+
+    let sentences = "1\tjpn\t今日は。\n2\teng\tHello.\n";
+    let tatoeba = Tatoeba::from_reader(sentences.as_bytes(), Some(|l: &str| l == "jpn")).unwrap();
+    assert_eq!(tatoeba.entries.len(), 1);
+    assert_eq!(tatoeba.entries[0].content, "今日は。");
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn kanjidic_gzip() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(KANJIDIC_SAMPLE.as_bytes()).unwrap();
+    let bytes = encoder.finish().unwrap();
+
+    let dict = Kanjidic::from_gzip_bytes(&bytes).unwrap();
+    assert_eq!(dict.entries.len(), kanjidic_sample().entries.len());
+}
+
+#[cfg(feature = "fs")]
 #[test]
 fn tatoeba_works() {
     let cwd = env::current_dir().unwrap();
@@ -1,8 +1,8 @@
 use crate::errors::{ParseError, XmlError};
 use roxmltree::{ExpandedName, Node, NS_XML_URI};
 use std::borrow::Cow;
+#[cfg(feature = "fs")]
 use std::fs;
-use std::io;
 
 macro_rules! const_strs {
     ( $( $id:ident : $val:expr ),* $(,)? ) => {
@@ -10,30 +10,108 @@ macro_rules! const_strs {
     };
 }
 
-pub fn find_child_tag<'a>(n: Node<'a, 'a>, tag_name: &str) -> Option<Node<'a, 'a>> {
+pub(crate) fn find_child_tag<'a>(n: Node<'a, 'a>, tag_name: &str) -> Option<Node<'a, 'a>> {
     n.children().find(|c| c.tag_name().name() == tag_name)
 }
 
-pub fn find_child_tag_err<'a>(n: Node<'a, 'a>, tag_name: &str) -> Result<Node<'a, 'a>, ParseError> {
-    find_child_tag(n, tag_name).ok_or(XmlError::MissingTag(tag_name.to_owned()).into())
+pub(crate) fn find_child_tag_err<'a>(
+    n: Node<'a, 'a>,
+    tag_name: &str,
+) -> Result<Node<'a, 'a>, ParseError> {
+    find_child_tag(n, tag_name).ok_or_else(|| missing_tag(n, tag_name).into())
 }
 
-pub fn get_node_attr<'a>(n: Node<'a, 'a>, attr_name: &str) -> Result<Cow<'a, str>, ParseError> {
+// Computing the text position scans the document up to the node, so this
+// should only be called once the tag is known to be missing.
+pub(crate) fn missing_tag(parent: Node, tag_name: &str) -> XmlError {
+    let pos = parent.document().text_pos_at(parent.range().start);
+    XmlError::MissingTag {
+        tag: tag_name.to_owned(),
+        pos: Some(pos),
+    }
+}
+
+pub(crate) fn get_node_attr<'a>(
+    n: Node<'a, 'a>,
+    attr_name: &str,
+) -> Result<Cow<'a, str>, ParseError> {
     n.attribute(attr_name)
         .ok_or(XmlError::MissingAttr(attr_name.to_owned()).into())
         .map(|t| t.into())
 }
 
-pub fn get_node_text<'a>(n: Node<'a, 'a>) -> Result<Cow<'a, str>, ParseError> {
+pub(crate) fn get_node_attr_opt<'a>(n: Node<'a, 'a>, attr_name: &str) -> Option<Cow<'a, str>> {
+    n.attribute(attr_name).map(|t| t.into())
+}
+
+// Errors if the element has no text, as for <tag/> or <tag></tag>. Text that is
+// only whitespace is returned as is; use get_node_text_opt where an element may
+// be legitimately blank.
+pub(crate) fn get_node_text<'a>(n: Node<'a, 'a>) -> Result<Cow<'a, str>, ParseError> {
     n.text()
         .ok_or(XmlError::MissingText.into())
         .map(|t| t.into())
 }
 
-pub fn ns_xml_attr(attr: &str) -> ExpandedName {
+// Returns None for an element with no text or only whitespace, so that blank
+// elements can be skipped.
+pub(crate) fn get_node_text_opt<'a>(n: Node<'a, 'a>) -> Option<Cow<'a, str>> {
+    n.text().filter(|t| !t.trim().is_empty()).map(|t| t.into())
+}
+
+pub(crate) fn ns_xml_attr(attr: &str) -> ExpandedName<'_> {
     (NS_XML_URI, attr).into()
 }
 
-pub fn read_file(filepath: &str) -> Result<String, io::Error> {
-    fs::read_to_string(filepath)
+#[cfg(feature = "fs")]
+pub(crate) fn read_file(filepath: &str) -> Result<String, ParseError> {
+    fs::read_to_string(filepath).map_err(|err| ParseError::io(filepath, err))
+}
+
+const KATAKANA_OFFSET: u32 = 0x60;
+
+pub(crate) fn is_hiragana(c: char) -> bool {
+    // ぁ-ゖ and the iteration marks ゝゞ.
+    matches!(c, '\u{3041}'..='\u{3096}' | '\u{309D}'..='\u{309E}')
+}
+
+pub(crate) fn is_katakana(c: char) -> bool {
+    // ァ-ヶ and the iteration marks ヽヾ.
+    matches!(c, '\u{30A1}'..='\u{30F6}' | '\u{30FD}'..='\u{30FE}')
+}
+
+pub(crate) fn is_kanji(c: char) -> bool {
+    // CJK Unified Ideographs and Extensions A and B. The compatibility
+    // ideographs and the radical blocks are not included.
+    matches!(
+        c,
+        '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '\u{20000}'..='\u{2A6DF}'
+    )
+}
+
+/// Converts katakana in the string to hiragana. Characters without a hiragana
+/// counterpart, such as the prolonged sound mark, are left as is.
+pub fn to_hiragana(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if is_katakana(c) {
+                std::char::from_u32(c as u32 - KATAKANA_OFFSET).unwrap_or(c)
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Converts hiragana in the string to katakana.
+pub fn to_katakana(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if is_hiragana(c) {
+                std::char::from_u32(c as u32 + KATAKANA_OFFSET).unwrap_or(c)
+            } else {
+                c
+            }
+        })
+        .collect()
 }
@@ -0,0 +1,18 @@
+// Hits the network, so only runs when JMDICT_TEST_KANJIDIC_URL is set, e.g. to
+// http://www.edrdg.org/kanjidic/kanjidic2.xml.gz. It is an integration test
+// rather than part of src/tests.rs so that the tokio dev-dependency is only
+// used here.
+#![cfg(feature = "download")]
+
+use jmdict::kanjidic::Kanjidic;
+
+#[tokio::test]
+async fn kanjidic_from_url() {
+    let url = match std::env::var("JMDICT_TEST_KANJIDIC_URL") {
+        Ok(url) => url,
+        Err(_) => return,
+    };
+
+    let dict = Kanjidic::from_url(&url).await.unwrap();
+    assert!(dict.find_literal("水").is_some());
+}